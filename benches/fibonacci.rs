@@ -31,21 +31,30 @@ fn fibonacci_bench(c: &mut Criterion) {
         ".wasm"
     ));
 
-    let contract = Contract::new(Fibonacci, code.to_vec());
-
-    let mut network = NetworkState::default();
-
-    let contract_id = network.deploy(contract).unwrap();
-    let mut gas = GasMeter::with_limit(1_000_000_000_000);
-    c.bench_function("fibonacci 3", |b| {
-        b.iter(|| {
-            fibonacci_3(
-                black_box(&mut network),
-                black_box(contract_id),
-                black_box(&mut gas),
-            )
-        })
-    });
+    // Compare against parsing the module on every call
+    let networks = vec![
+        ("fibonacci 3", NetworkState::default()),
+        (
+            "fibonacci 3 (no module cache)",
+            NetworkState::default().with_module_cache_capacity(0),
+        ),
+    ];
+
+    for (name, mut network) in networks {
+        let contract = Contract::new(Fibonacci, code.to_vec());
+
+        let contract_id = network.deploy(contract).unwrap();
+        let mut gas = GasMeter::with_limit(1_000_000_000_000);
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                fibonacci_3(
+                    black_box(&mut network),
+                    black_box(contract_id),
+                    black_box(&mut gas),
+                )
+            })
+        });
+    }
 }
 
 criterion_main!(fibonacci_main);
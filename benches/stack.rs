@@ -33,29 +33,36 @@ fn stack_64(
 fn stack_bench(c: &mut Criterion) {
     type Leaf = u64;
 
-    let stack = Stack::<Leaf>::new();
-
     let code = include_bytes!(concat!(
         "../target/wasm32-unknown-unknown/release/",
         "stack",
         ".wasm"
     ));
 
-    let contract = Contract::new(stack, code.to_vec());
-
-    let mut network = NetworkState::default();
-
-    let contract_id = network.deploy(contract).unwrap();
-    let mut gas = GasMeter::with_limit(1_000_000_000_000);
-    c.bench_function("stack 64", |b| {
-        b.iter(|| {
-            stack_64(
-                black_box(&mut network),
-                black_box(contract_id),
-                black_box(&mut gas),
-            )
-        })
-    });
+    // Compare against parsing the module on every call
+    let networks = vec![
+        ("stack 64", NetworkState::default()),
+        (
+            "stack 64 (no module cache)",
+            NetworkState::default().with_module_cache_capacity(0),
+        ),
+    ];
+
+    for (name, mut network) in networks {
+        let contract = Contract::new(Stack::<Leaf>::new(), code.to_vec());
+
+        let contract_id = network.deploy(contract).unwrap();
+        let mut gas = GasMeter::with_limit(1_000_000_000_000);
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                stack_64(
+                    black_box(&mut network),
+                    black_box(contract_id),
+                    black_box(&mut gas),
+                )
+            })
+        });
+    }
 }
 
 criterion_main!(stack_main);
@@ -4,7 +4,7 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use canonical::{Canon, CanonError, Sink, Source};
+use canonical::{Canon, Sink, Source};
 use dusk_abi::{ContractState, Query, ReturnValue, Transaction};
 
 use wasmi::{
@@ -13,7 +13,10 @@ use wasmi::{
 };
 
 use crate::contract::ContractId;
-use crate::gas::GasMeter;
+use crate::event::Event;
+use crate::gas::{Gas, GasMeter};
+use crate::memory;
+use crate::receipt::{Call, CallKind};
 use crate::state::NetworkState;
 use crate::VMError;
 
@@ -25,6 +28,7 @@ pub use crate::resolver::CompoundResolver as StandardABI;
 enum Argument {
     Query(Query),
     Transaction(Transaction),
+    Hook(&'static str),
 }
 
 pub struct StackFrame {
@@ -32,6 +36,13 @@ pub struct StackFrame {
     argument: Argument,
     ret: ReturnValue,
     memory: MemoryRef,
+    /// Whether the frame is not allowed to mutate the state
+    read_only: bool,
+    /// The value transferred to the callee along with the call
+    value: u64,
+    /// Whether the callee's code was upgraded during the call, its state must
+    /// be migrated once it returns
+    pending_migration: bool,
 }
 
 impl std::fmt::Debug for StackFrame {
@@ -47,6 +58,9 @@ impl StackFrame {
             memory,
             argument: Argument::Query(query),
             ret: Default::default(),
+            read_only: true,
+            value: 0,
+            pending_migration: false,
         }
     }
 
@@ -54,12 +68,33 @@ impl StackFrame {
         callee: ContractId,
         memory: MemoryRef,
         transaction: Transaction,
+        read_only: bool,
+        value: u64,
     ) -> Self {
         StackFrame {
             callee,
             memory,
             argument: Argument::Transaction(transaction),
             ret: Default::default(),
+            read_only,
+            value,
+            pending_migration: false,
+        }
+    }
+
+    fn new_hook(
+        callee: ContractId,
+        memory: MemoryRef,
+        export: &'static str,
+    ) -> Self {
+        StackFrame {
+            callee,
+            memory,
+            argument: Argument::Hook(export),
+            ret: Default::default(),
+            read_only: false,
+            value: 0,
+            pending_migration: false,
         }
     }
 
@@ -84,6 +119,12 @@ pub struct CallContext<'a> {
     state: &'a mut NetworkState,
     stack: Vec<StackFrame>,
     gas_meter: &'a mut GasMeter,
+    gas_frames: Vec<GasMeter>,
+    events: Vec<Event>,
+    calls: Vec<Call>,
+    pending_calls: Vec<(Call, Gas)>,
+    /// The contracts destructed, along with the beneficiary of their balance
+    destructed: Vec<(ContractId, ContractId)>,
 }
 
 impl<'a> CallContext<'a> {
@@ -95,6 +136,11 @@ impl<'a> CallContext<'a> {
             state,
             stack: vec![],
             gas_meter,
+            gas_frames: vec![],
+            events: vec![],
+            calls: vec![],
+            pending_calls: vec![],
+            destructed: vec![],
         }
     }
 
@@ -103,130 +149,359 @@ impl<'a> CallContext<'a> {
         target: ContractId,
         query: Query,
     ) -> Result<ReturnValue, VMError> {
+        let depth = self.stack.len();
+        let events = self.events.len();
+        let destructed = self.destructed.len();
+
+        self.begin_call(target, CallKind::Query);
+
+        let result = self.execute_query(target, query);
+
+        if result.is_err() {
+            self.unwind(depth, events, destructed);
+        }
+
+        self.end_call();
+        result
+    }
+
+    pub fn transact(
+        &mut self,
+        target: ContractId,
+        transaction: Transaction,
+    ) -> Result<(ContractState, ReturnValue), VMError> {
+        self.call_transaction(None, target, transaction, 0)
+    }
+
+    /// Transacts with the target, moving `value` from the balance of the
+    /// sender to the balance of the target along with the call.
+    ///
+    /// The transfer is reverted if the transaction fails.
+    pub fn transact_with_value(
+        &mut self,
+        sender: ContractId,
+        target: ContractId,
+        transaction: Transaction,
+        value: u64,
+    ) -> Result<(ContractState, ReturnValue), VMError> {
+        self.call_transaction(Some(sender), target, transaction, value)
+    }
+
+    fn call_transaction(
+        &mut self,
+        sender: Option<ContractId>,
+        target: ContractId,
+        transaction: Transaction,
+        value: u64,
+    ) -> Result<(ContractState, ReturnValue), VMError> {
+        let depth = self.stack.len();
+        let events = self.events.len();
+        let destructed = self.destructed.len();
+
+        self.begin_call(target, CallKind::Transaction);
+        self.state.begin_checkpoint();
+
+        let result = match sender {
+            Some(sender) => self.transfer(sender, target, value),
+            None => Ok(()),
+        }
+        .and_then(|_| self.execute_transaction(target, transaction, value));
+
+        let result = match result {
+            Ok(ok) => {
+                self.state.commit_checkpoint();
+                Ok(ok)
+            }
+            Err(err) => {
+                self.unwind(depth, events, destructed);
+                self.state.revert_checkpoint().and(Err(err))
+            }
+        };
+
+        self.end_call();
+        result
+    }
+
+    /// Moves `value` from the sender to the target, failing if the contract
+    /// being executed is not allowed to mutate the state
+    fn transfer(
+        &mut self,
+        sender: ContractId,
+        target: ContractId,
+        value: u64,
+    ) -> Result<(), VMError> {
+        if value == 0 {
+            return Ok(());
+        }
+
+        self.check_mutable()?;
+        self.state.transfer(&sender, &target, value)
+    }
+
+    /// Migrates the state of the target to its current code, by calling the
+    /// `migrate` export of the code if there is one.
+    ///
+    /// If the target is being executed further up the stack, the migration
+    /// is deferred until it returns, so that the state it returns is the one
+    /// being migrated.
+    pub fn migrate(&mut self, target: ContractId) -> Result<(), VMError> {
+        if let Some(frame) = self
+            .stack
+            .iter_mut()
+            .rev()
+            .find(|frame| frame.callee == target)
+        {
+            frame.pending_migration = true;
+            return Ok(());
+        }
+
+        self.call_hook(target, CallKind::Migration, "migrate", &[])
+    }
+
+    /// Initializes the state of a newly deployed contract, by calling the
+    /// `init` export of its code with the given constructor arguments, if
+    /// there is one
+    pub fn init(
+        &mut self,
+        target: ContractId,
+        arguments: &[u8],
+    ) -> Result<(), VMError> {
+        self.call_hook(target, CallKind::Init, "init", arguments)
+    }
+
+    fn call_hook(
+        &mut self,
+        target: ContractId,
+        kind: CallKind,
+        export: &'static str,
+        argument: &[u8],
+    ) -> Result<(), VMError> {
+        let depth = self.stack.len();
+        let events = self.events.len();
+        let destructed = self.destructed.len();
+
+        self.begin_call(target, kind);
+
+        let result = self.execute_hook(target, export, argument);
+
+        if result.is_err() {
+            self.unwind(depth, events, destructed);
+        }
+
+        self.end_call();
+        result
+    }
+
+    /// Performs `call` metered by a nested gas meter, limited to `gas_limit`
+    /// or to the gas left minus the reserve of the caller, whichever is
+    /// lower. Once the call returns, the gas it spent is charged to the parent
+    /// meter, so running out of gas in the call leaves the reserve of the
+    /// caller untouched.
+    pub fn with_gas_limit<R, C>(
+        &mut self,
+        gas_limit: Gas,
+        call: C,
+    ) -> Result<R, VMError>
+    where
+        C: FnOnce(&mut Self) -> Result<R, VMError>,
+    {
+        let gas_limit = gas_limit.min(self.nested_gas_left());
+        self.gas_frames.push(GasMeter::with_limit(gas_limit));
+
+        let result = call(self);
+
+        let frame = self.gas_frames.pop().expect("Invalid gas frames");
+        self.charge(frame.spent()).and(result)
+    }
+
+    /// Returns the gas a nested call can spend, that is the gas left minus the
+    /// reserve kept back by the caller, as set by the schedule
+    fn nested_gas_left(&self) -> Gas {
+        let left = self.gas_meter().left();
+        let divisor = self.state.schedule().call_gas_reserve_divisor;
+
+        left - left.checked_div(divisor).unwrap_or(0)
+    }
+
+    /// Discards the stack frames, events and self-destructions left behind by
+    /// a failed call, so the caller can carry on
+    fn unwind(&mut self, depth: usize, events: usize, destructed: usize) {
+        self.stack.truncate(depth);
+        self.events.truncate(events);
+        self.destructed.truncate(destructed);
+    }
+
+    /// Marks the contract being executed for removal from the state, once
+    /// the transaction is successfully committed, its balance is credited to
+    /// the beneficiary then.
+    ///
+    /// Fails with [`VMError::UnknownContract`] if the beneficiary is not
+    /// deployed, and with [`VMError::InvalidBeneficiary`] if it is the callee
+    /// or a contract already destructed, since the balance would be lost. The
+    /// beneficiary can still destruct itself later in the transaction,
+    /// passing the balance on to its own beneficiary.
+    pub fn self_destruct(
+        &mut self,
+        beneficiary: ContractId,
+    ) -> Result<(), VMError> {
+        self.check_mutable()?;
+
+        let callee = *self.callee();
+        let destructed = |id| self.destructed.iter().any(|(d, _)| *d == id);
+
+        if beneficiary == callee || destructed(beneficiary) {
+            return Err(VMError::InvalidBeneficiary);
+        }
+        self.state.get_contract(&beneficiary)?;
+
+        if !destructed(callee) {
+            self.destructed.push((callee, beneficiary));
+        }
+
+        Ok(())
+    }
+
+    fn begin_call(&mut self, contract: ContractId, kind: CallKind) {
+        let spent = self.gas_meter().spent();
+        self.pending_calls.push((Call::new(contract, kind), spent));
+    }
+
+    fn end_call(&mut self) {
+        if let Some((mut call, spent)) = self.pending_calls.pop() {
+            call.gas_spent = self.gas_meter().spent() - spent;
+
+            match self.pending_calls.last_mut() {
+                Some((parent, _)) => parent.calls.push(call),
+                None => self.calls.push(call),
+            }
+        }
+    }
+
+    fn execute_query(
+        &mut self,
+        target: ContractId,
+        query: Query,
+    ) -> Result<ReturnValue, VMError> {
+        self.charge(self.state.schedule().call_base_cost)?;
+
+        if let Some(module) = self.state.modules().borrow().get(&target) {
+            // is this a reserved module call?
+            return module.execute(query).map_err(VMError::from_store_error);
+        }
+
         let resolver = StandardABI::default();
         let imports = ImportsBuilder::new()
             .with_resolver("env", &resolver)
             .with_resolver("canon", &resolver);
 
-        let instance;
+        let module = self.state.get_module(&target)?;
 
-        if let Some(module) = self.state.modules().borrow().get(&target) {
-            // is this a reserved module call?
-            return module.execute(query).map_err(VMError::from_store_error);
-        } else {
+        let instance =
+            wasmi::ModuleInstance::new(&module, &imports)?.assert_no_start();
+
+        let memref = match instance.export_by_name("memory") {
+            Some(wasmi::ExternVal::Memory(memref)) => memref,
+            _ => return Err(VMError::MemoryNotFound),
+        };
+
+        let written = {
             let contract = self.state.get_contract(&target)?;
+            write_input(&memref, contract.state().as_bytes(), query.as_bytes())?
+        };
 
-            let module = wasmi::Module::from_buffer(contract.bytecode())?;
-
-            instance = wasmi::ModuleInstance::new(&module, &imports)?
-                .assert_no_start();
-
-            match instance.export_by_name("memory") {
-                Some(wasmi::ExternVal::Memory(memref)) => {
-                    // write contract state and argument to memory
-                    memref
-                        .with_direct_access_mut(|m| {
-                            let mut sink = Sink::new(&mut *m);
-                            // copy the raw bytes only, since the
-                            // contract
-                            // can infer
-                            // it's own state and argument lengths
-                            sink.copy_bytes(contract.state().as_bytes());
-                            sink.copy_bytes(query.as_bytes());
-                            Ok(())
-                        })
-                        .map_err(VMError::from_store_error)?;
-
-                    self.stack
-                        .push(StackFrame::new_query(target, memref, query));
-                }
-                _ => return Err(VMError::MemoryNotFound),
-            }
-        }
+        self.charge(self.state.schedule().instantiate_base_cost)?;
+        self.charge_write(written)?;
+
+        self.push_frame(StackFrame::new_query(target, memref.clone(), query))?;
 
         // Perform the query call
         instance.invoke_export("q", &[wasmi::RuntimeValue::I32(0)], self)?;
 
-        match instance.export_by_name("memory") {
-            Some(wasmi::ExternVal::Memory(memref)) => memref
-                .with_direct_access_mut(|m| {
-                    let mut source = Source::new(&m[..]);
-                    let result = ReturnValue::decode(&mut source)?;
-
-                    self.stack.pop();
-                    Ok(result)
-                })
-                .map_err(VMError::from_store_error),
-            _ => Err(VMError::MemoryNotFound),
-        }
+        let result = memref
+            .with_direct_access(|m| {
+                let mut source = Source::new(&m[..]);
+                ReturnValue::decode(&mut source)
+            })
+            .map_err(VMError::from_store_error)?;
+
+        self.stack.pop();
+
+        self.charge_return(result.as_bytes().len())?;
+
+        Ok(result)
     }
 
-    pub fn transact(
+    fn execute_transaction(
         &mut self,
         target: ContractId,
         transaction: Transaction,
+        value: u64,
     ) -> Result<(ContractState, ReturnValue), VMError> {
+        self.charge(self.state.schedule().call_base_cost)?;
+
         let resolver = StandardABI::default();
         let imports = ImportsBuilder::new()
             .with_resolver("env", &resolver)
             .with_resolver("canon", &resolver);
 
-        let instance;
+        let module = self.state.get_module(&target)?;
 
-        {
+        let instance =
+            wasmi::ModuleInstance::new(&module, &imports)?.assert_no_start();
+
+        let memref = match instance.export_by_name("memory") {
+            Some(wasmi::ExternVal::Memory(memref)) => memref,
+            _ => return Err(VMError::MemoryNotFound),
+        };
+
+        let written = {
             let contract = self.state.get_contract(&target)?;
-            let module = wasmi::Module::from_buffer(contract.bytecode())?;
-
-            instance = wasmi::ModuleInstance::new(&module, &imports)?
-                .assert_no_start();
-
-            match instance.export_by_name("memory") {
-                Some(wasmi::ExternVal::Memory(memref)) => {
-                    // write contract state and argument to memory
-
-                    memref.with_direct_access_mut(|m| {
-                        let mut sink = Sink::new(&mut *m);
-                        // copy the raw bytes only, since the contract can
-                        // infer it's own state and argument lengths.
-                        sink.copy_bytes(contract.state().as_bytes());
-                        sink.copy_bytes(transaction.as_bytes());
-                    });
-
-                    self.stack.push(StackFrame::new_transaction(
-                        target,
-                        memref,
-                        transaction,
-                    ));
-                }
-                _ => return Err(VMError::MemoryNotFound),
-            }
-        }
+            write_input(
+                &memref,
+                contract.state().as_bytes(),
+                transaction.as_bytes(),
+            )?
+        };
+
+        self.charge(self.state.schedule().instantiate_base_cost)?;
+        self.charge_write(written)?;
+
+        // a transaction called from a read-only frame is read-only as well
+        let read_only = self.stack.last().map_or(false, |top| top.read_only);
+
+        self.push_frame(StackFrame::new_transaction(
+            target,
+            memref.clone(),
+            transaction,
+            read_only,
+            value,
+        ))?;
+
         // Perform the transact call
         instance.invoke_export("t", &[wasmi::RuntimeValue::I32(0)], self)?;
 
-        let ret = {
-            let mut contract = self.state.get_contract_mut(&target)?;
+        let (state, ret) = memref
+            .with_direct_access(|m| {
+                let mut source = Source::new(&m[..]);
 
-            match instance.export_by_name("memory") {
-                Some(wasmi::ExternVal::Memory(memref)) => {
-                    memref
-                        .with_direct_access_mut(|m| {
-                            let mut source = Source::new(&m[..]);
+                // read new state
+                let state = ContractState::decode(&mut source)?;
 
-                            // read new state
-                            let state = ContractState::decode(&mut source)?;
+                // read return value
+                Ok((state, ReturnValue::decode(&mut source)))
+            })
+            .map_err(VMError::from_store_error)?;
 
-                            // update new self state
-                            *(*contract).state_mut() = state;
+        self.charge_read(state.as_bytes().len())?;
 
-                            // read return value
-                            ReturnValue::decode(&mut source)
-                        })
-                        .map_err(VMError::from_store_error)
-                }
-                _ => return Err(VMError::MemoryNotFound),
-            }
-        };
+        // update new self state
+        *self.state.get_contract_mut(&target)?.state_mut() = state;
+
+        if self.top().pending_migration {
+            self.call_hook(target, CallKind::Migration, "migrate", &[])?;
+        }
+
+        let ret = ret.map_err(VMError::from_store_error)?;
 
         let state = if self.stack.len() > 1 {
             self.stack.pop();
@@ -237,21 +512,167 @@ impl<'a> CallContext<'a> {
             state
         };
 
-        Ok((state, ret?))
+        self.charge_return(ret.as_bytes().len())?;
+
+        Ok((state, ret))
+    }
+
+    /// Calls the given export of the target's code with its state followed by
+    /// the argument, and replaces the state with the one it returns
+    fn execute_hook(
+        &mut self,
+        target: ContractId,
+        export: &'static str,
+        argument: &[u8],
+    ) -> Result<(), VMError> {
+        self.charge(self.state.schedule().call_base_cost)?;
+
+        let resolver = StandardABI::default();
+        let imports = ImportsBuilder::new()
+            .with_resolver("env", &resolver)
+            .with_resolver("canon", &resolver);
+
+        let module = self.state.get_module(&target)?;
+
+        let instance =
+            wasmi::ModuleInstance::new(&module, &imports)?.assert_no_start();
+
+        // the state is kept as it is by code that doesn't export the hook
+        if instance.export_by_name(export).is_none() {
+            return Ok(());
+        }
+
+        let memref = match instance.export_by_name("memory") {
+            Some(wasmi::ExternVal::Memory(memref)) => memref,
+            _ => return Err(VMError::MemoryNotFound),
+        };
+
+        let written = {
+            let contract = self.state.get_contract(&target)?;
+            write_input(&memref, contract.state().as_bytes(), argument)?
+        };
+
+        self.charge(self.state.schedule().instantiate_base_cost)?;
+        self.charge_write(written)?;
+
+        self.push_frame(StackFrame::new_hook(target, memref.clone(), export))?;
+
+        // Perform the hook call
+        instance.invoke_export(export, &[wasmi::RuntimeValue::I32(0)], self)?;
+
+        let state = memref
+            .with_direct_access(|m| {
+                let mut source = Source::new(&m[..]);
+                ContractState::decode(&mut source)
+            })
+            .map_err(VMError::from_store_error)?;
+
+        self.charge_read(state.as_bytes().len())?;
+
+        *self.state.get_contract_mut(&target)?.state_mut() = state;
+
+        self.stack.pop();
+
+        Ok(())
+    }
+
+    /// Pushes a frame on the call stack, failing with
+    /// [`VMError::CallDepthExceeded`] if the stack is already at the maximum
+    /// depth allowed by the schedule
+    fn push_frame(&mut self, frame: StackFrame) -> Result<(), VMError> {
+        if self.stack.len() >= self.state.schedule().max_call_depth as usize {
+            return Err(VMError::CallDepthExceeded);
+        }
+
+        self.stack.push(frame);
+        Ok(())
+    }
+
+    /// Charges `gas` to the meter, failing with [`VMError::OutOfGas`] when the
+    /// budget is exhausted.
+    pub fn charge(&mut self, gas: Gas) -> Result<(), VMError> {
+        if self.gas_meter_mut().charge(gas).is_out_of_gas() {
+            return Err(VMError::OutOfGas);
+        }
+        Ok(())
+    }
+
+    /// Charges for `len` bytes read by the host from the sandbox memory
+    pub fn charge_read(&mut self, len: usize) -> Result<(), VMError> {
+        let cost = self.state.schedule().sandbox_data_read_cost;
+        self.charge(cost.saturating_mul(len as Gas))
+    }
+
+    /// Charges for `len` bytes written by the host into the sandbox memory
+    pub fn charge_write(&mut self, len: usize) -> Result<(), VMError> {
+        let cost = self.state.schedule().sandbox_data_write_cost;
+        self.charge(cost.saturating_mul(len as Gas))
+    }
+
+    /// Charges for `len` bytes of data returned by a contract call
+    pub fn charge_return(&mut self, len: usize) -> Result<(), VMError> {
+        let cost = self.state.schedule().return_data_per_byte_cost;
+        self.charge(cost.saturating_mul(len as Gas))
+    }
+
+    /// Records an event emitted by the contract being executed
+    pub fn emit(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Consumes the context, returning the events emitted and the tree of
+    /// calls performed
+    pub fn into_parts(
+        self,
+    ) -> (Vec<Event>, Vec<Call>, Vec<(ContractId, ContractId)>) {
+        (self.events, self.calls, self.destructed)
     }
 
     pub fn gas_meter(&self) -> &GasMeter {
-        self.gas_meter
+        match self.gas_frames.last() {
+            Some(frame) => frame,
+            None => &*self.gas_meter,
+        }
     }
 
     pub fn gas_meter_mut(&mut self) -> &mut GasMeter {
-        self.gas_meter
+        match self.gas_frames.last_mut() {
+            Some(frame) => frame,
+            None => &mut *self.gas_meter,
+        }
+    }
+
+    /// Returns the state along with the gas meter in use, so the state can
+    /// charge for what is put into it
+    pub fn state_and_gas_meter(
+        &mut self,
+    ) -> (&mut NetworkState, &mut GasMeter) {
+        let gas_meter = match self.gas_frames.last_mut() {
+            Some(frame) => frame,
+            None => &mut *self.gas_meter,
+        };
+
+        (&mut *self.state, gas_meter)
     }
 
     pub fn top(&self) -> &StackFrame {
         self.stack.last().expect("Invalid stack")
     }
 
+    /// Fails with [`VMError::StateMutationInQuery`] if the contract being
+    /// executed is not allowed to mutate the state
+    pub fn check_mutable(&self) -> Result<(), VMError> {
+        match self.stack.last() {
+            Some(top) if top.read_only => Err(VMError::StateMutationInQuery),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the value transferred along with the call being executed
+    pub fn value(&self) -> u64 {
+        self.top().value
+    }
+
     pub fn callee(&self) -> &ContractId {
         &self.top().callee
     }
@@ -268,10 +689,10 @@ impl<'a> CallContext<'a> {
         self.top().memory(closure)
     }
 
-    pub fn memory_mut<R, C: FnOnce(&mut [u8]) -> Result<R, CanonError>>(
+    pub fn memory_mut<R, C: FnOnce(&mut [u8]) -> R>(
         &mut self,
         closure: C,
-    ) -> Result<R, CanonError> {
+    ) -> R {
         self.stack
             .last_mut()
             .expect("Invalid stack")
@@ -287,6 +708,26 @@ impl<'a> CallContext<'a> {
     }
 }
 
+/// Writes the contract state followed by the call argument at the start of
+/// the contract memory, returning the number of bytes written
+fn write_input(
+    memref: &MemoryRef,
+    state: &[u8],
+    argument: &[u8],
+) -> Result<usize, VMError> {
+    memref.with_direct_access_mut(|m| {
+        let len = state.len() + argument.len();
+        let mut sink = Sink::new(memory::slice_mut(m, 0, len)?);
+
+        // copy the raw bytes only, since the contract can infer it's own
+        // state and argument lengths
+        sink.copy_bytes(state);
+        sink.copy_bytes(argument);
+
+        Ok(len)
+    })
+}
+
 /// Convenience function to construct host traps
 pub fn host_trap(host: VMError) -> Trap {
     Trap::new(TrapKind::Host(Box::new(host)))
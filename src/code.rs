@@ -0,0 +1,121 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::module_config::DeployPolicy;
+use crate::{Schedule, VMError};
+use canonical_derive::Canon;
+
+/// Hash of a contract bytecode, under which the code is stored in the
+/// [`NetworkState`]
+///
+/// [`NetworkState`]: crate::NetworkState
+pub type CodeHash = [u8; 32];
+
+/// The bytecode of a contract, stored once in the [`NetworkState`] for all
+/// the contracts sharing it
+///
+/// [`NetworkState`]: crate::NetworkState
+#[derive(Clone, Canon)]
+pub struct Code {
+    bytecode: Vec<u8>,
+    instrumented: Vec<u8>,
+    schedule_version: u32,
+    policy_id: u8,
+    index: u64,
+    references: u64,
+}
+
+impl Code {
+    pub(crate) fn new(bytecode: Vec<u8>, index: u64) -> Self {
+        Code {
+            bytecode,
+            instrumented: vec![],
+            schedule_version: 0,
+            policy_id: 0,
+            index,
+            references: 0,
+        }
+    }
+
+    /// Returns a reference to the bytecode, as it was deployed
+    pub fn bytecode(&self) -> &[u8] {
+        &self.bytecode
+    }
+
+    /// Returns a reference to the instrumented bytecode executed by the VM
+    pub fn instrumented_bytecode(&self) -> &[u8] {
+        &self.instrumented
+    }
+
+    /// Returns the version of the [`Schedule`] the code was instrumented with
+    pub fn schedule_version(&self) -> u32 {
+        self.schedule_version
+    }
+
+    /// Returns the id of the [`DeployPolicy`] the code was instrumented with
+    pub fn policy_id(&self) -> u8 {
+        self.policy_id
+    }
+
+    /// Returns whether the code was instrumented under the given
+    /// [`Schedule`] and [`DeployPolicy`]
+    pub(crate) fn is_instrumented_under(
+        &self,
+        schedule: &Schedule,
+        policy: &DeployPolicy,
+    ) -> bool {
+        self.schedule_version == schedule.version
+            && self.policy_id == policy.id()
+    }
+
+    /// Returns the position of the code in the order codes were stored in
+    /// the [`NetworkState`]
+    ///
+    /// [`NetworkState`]: crate::NetworkState
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the number of contracts running the code
+    pub fn references(&self) -> u64 {
+        self.references
+    }
+
+    pub(crate) fn retain(&mut self) {
+        self.references += 1;
+    }
+
+    /// Releases a reference to the code, returning the number of references
+    /// left
+    pub(crate) fn release(&mut self) -> u64 {
+        self.references = self.references.saturating_sub(1);
+        self.references
+    }
+
+    /// Instruments the original bytecode under the given [`Schedule`] and
+    /// [`DeployPolicy`]
+    pub(crate) fn instrument(
+        &mut self,
+        schedule: &Schedule,
+        policy: &DeployPolicy,
+    ) -> Result<(), VMError> {
+        self.instrumented = self.instrumented_under(schedule, policy)?;
+        self.schedule_version = schedule.version;
+        self.policy_id = policy.id();
+
+        Ok(())
+    }
+
+    /// Returns the original bytecode instrumented under the given
+    /// [`Schedule`] and [`DeployPolicy`], leaving the code untouched
+    pub(crate) fn instrumented_under(
+        &self,
+        schedule: &Schedule,
+        policy: &DeployPolicy,
+    ) -> Result<Vec<u8>, VMError> {
+        Ok(policy.module_config().apply(&self.bytecode[..], schedule)?)
+    }
+}
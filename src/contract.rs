@@ -4,9 +4,9 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-use crate::module_config::ModuleConfig;
+use crate::code::CodeHash;
 use crate::VMError;
-use canonical::Canon;
+use canonical::{Canon, Store};
 use canonical_derive::Canon;
 
 pub use dusk_abi::{ContractId, ContractState};
@@ -16,6 +16,10 @@ pub use dusk_abi::{ContractId, ContractState};
 pub struct Contract {
     state: ContractState,
     code: Vec<u8>,
+    code_hash: CodeHash,
+    nonce: u64,
+    admin: Option<ContractId>,
+    balance: u64,
 }
 
 impl Contract {
@@ -25,17 +29,60 @@ impl Contract {
         State: Canon,
         Code: Into<Vec<u8>>,
     {
+        Self::from_state(ContractState::from_canon(&state), code.into())
+    }
+
+    /// Create a new Contract with an already encoded initial state
+    pub(crate) fn from_state(state: ContractState, code: Vec<u8>) -> Self {
+        let code_hash = Store::hash(&code);
+
         Contract {
-            state: ContractState::from_canon(&state),
-            code: code.into(),
+            state,
+            code,
+            code_hash,
+            nonce: 0,
+            admin: None,
+            balance: 0,
         }
     }
 
-    /// Returns a reference to the contract bytecode
+    /// Designates the contract allowed to upgrade this contract's code,
+    /// besides the contract itself
+    pub fn with_admin(mut self, admin: ContractId) -> Self {
+        self.admin = Some(admin);
+        self
+    }
+
+    /// Returns the contract allowed to upgrade this contract's code, if any
+    pub fn admin(&self) -> Option<&ContractId> {
+        self.admin.as_ref()
+    }
+
+    /// Returns a reference to the bytecode carried by the contract.
+    ///
+    /// Once deployed, the bytecode is only kept in the code registry of the
+    /// [`NetworkState`], under the contract's [`CodeHash`].
+    ///
+    /// [`NetworkState`]: crate::NetworkState
     pub fn bytecode(&self) -> &[u8] {
         &self.code
     }
 
+    /// Returns the hash of the contract bytecode
+    pub fn code_hash(&self) -> &CodeHash {
+        &self.code_hash
+    }
+
+    /// Takes the bytecode carried by the contract, leaving it empty
+    pub(crate) fn take_code(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.code)
+    }
+
+    /// Makes the contract run the code with the given hash
+    pub(crate) fn set_code_hash(&mut self, code_hash: CodeHash) {
+        self.code_hash = code_hash;
+    }
+
     /// Returns a reference to the contract state
     pub fn state(&self) -> &ContractState {
         &self.state
@@ -46,14 +93,80 @@ impl Contract {
         &mut self.state
     }
 
-    pub(crate) fn instrument(mut self) -> Result<Self, VMError> {
-        self.code = ModuleConfig::new()
-            .with_grow_cost()
-            .with_forbidden_floats()
-            .with_metering()
-            .with_table_size_limit()
-            .apply(&self.code[..])?;
+    /// Returns the number of contracts deployed by this contract
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub(crate) fn increment_nonce(&mut self) {
+        self.nonce += 1;
+    }
+
+    /// Returns the native balance held by the contract
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    pub(crate) fn credit(&mut self, value: u64) -> Result<(), VMError> {
+        self.balance = self
+            .balance
+            .checked_add(value)
+            .ok_or(VMError::BalanceOverflow)?;
+        Ok(())
+    }
+
+    pub(crate) fn debit(&mut self, value: u64) -> Result<(), VMError> {
+        self.balance = self
+            .balance
+            .checked_sub(value)
+            .ok_or(VMError::NotEnoughFunds)?;
+        Ok(())
+    }
+
+    /// Derives the id of the contract deployed by `deployer` with the given
+    /// nonce, running the code with the given hash
+    pub fn derive_id(
+        deployer: &ContractId,
+        nonce: u64,
+        code_hash: &CodeHash,
+    ) -> ContractId {
+        let mut preimage = deployer.as_bytes().to_vec();
+        preimage.extend_from_slice(&nonce.to_le_bytes());
+        preimage.extend_from_slice(code_hash);
 
-        Ok(self)
+        Store::hash(&preimage).into()
+    }
+
+    /// Derives the id of the contract deployed by `deployer` with the given
+    /// salt, running the code with the given hash.
+    ///
+    /// Unlike [`Contract::derive_id`], the id does not depend on the number of
+    /// contracts previously deployed, so it can be known in advance.
+    pub fn derive_id_with_salt(
+        deployer: &ContractId,
+        salt: &[u8; 32],
+        code_hash: &CodeHash,
+    ) -> ContractId {
+        let mut preimage = deployer.as_bytes().to_vec();
+        preimage.extend_from_slice(salt);
+        preimage.extend_from_slice(code_hash);
+
+        Store::hash(&preimage).into()
+    }
+
+    /// Returns a contract running the code with the given hash, without
+    /// carrying the bytecode
+    pub(crate) fn with_code_hash(
+        state: ContractState,
+        code_hash: CodeHash,
+    ) -> Self {
+        Contract {
+            state,
+            code: vec![],
+            code_hash,
+            nonce: 0,
+            admin: None,
+            balance: 0,
+        }
     }
 }
@@ -0,0 +1,49 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use canonical_derive::Canon;
+
+use crate::contract::ContractId;
+
+/// Size in bytes of an event topic
+pub const TOPIC_SIZE: usize = 32;
+
+/// An event emitted by a contract during a transaction
+#[derive(Clone, Debug, PartialEq, Canon)]
+pub struct Event {
+    contract: ContractId,
+    topics: Vec<[u8; TOPIC_SIZE]>,
+    data: Vec<u8>,
+}
+
+impl Event {
+    pub(crate) fn new(
+        contract: ContractId,
+        topics: Vec<[u8; TOPIC_SIZE]>,
+        data: Vec<u8>,
+    ) -> Self {
+        Event {
+            contract,
+            topics,
+            data,
+        }
+    }
+
+    /// Returns the id of the contract that emitted the event
+    pub fn contract(&self) -> &ContractId {
+        &self.contract
+    }
+
+    /// Returns the topics of the event
+    pub fn topics(&self) -> &[[u8; TOPIC_SIZE]] {
+        &self.topics
+    }
+
+    /// Returns the data attached to the event
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
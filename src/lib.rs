@@ -14,21 +14,35 @@ use std::{fmt, io};
 
 use canonical::CanonError;
 use failure::Fail;
+use wasmi::TrapKind;
 
 mod call_context;
+mod code;
 mod contract;
+mod event;
 mod gas;
+mod memory;
+mod module_cache;
 mod module_config;
 mod ops;
+mod receipt;
 mod resolver;
+mod schedule;
 mod state;
 
 pub use dusk_abi;
 
 pub use call_context::StandardABI;
+pub use code::{Code, CodeHash};
 pub use contract::{Contract, ContractId};
+pub use event::Event;
 pub use gas::{Gas, GasMeter};
+pub use module_config::{DeployPolicy, InstrumentalizationError};
+pub use ops::{CALL_FAILED, CALL_OUT_OF_GAS, CALL_PANICKED, CALL_SUCCEEDED};
+pub use receipt::{Call, CallKind, GasEstimate, Receipt, TransactionStatus};
 pub use state::NetworkState;
+#[cfg(feature = "persistence")]
+pub use state::NetworkStateId;
 
 #[derive(Fail)]
 /// The errors that can happen while executing the VM
@@ -39,10 +53,23 @@ pub enum VMError {
     ContractPanic(String),
     /// Could not find WASM memory
     MemoryNotFound,
+    /// A host call tried to access memory outside of the contract's memory
+    MemoryAccessOutOfBounds,
     /// Error during the instrumentalization
     InstrumentalizationError(module_config::InstrumentalizationError),
     /// Invalid ABI Call
     InvalidABICall,
+    /// The contract imports something that is not part of the ABI
+    InvalidImport {
+        /// Module name of the import
+        module: String,
+        /// Field name of the import
+        field: String,
+        /// What the ABI expects
+        expected: String,
+        /// What the contract imports
+        found: String,
+    },
     /// Invalid Utf8
     InvalidUtf8,
     /// Invalid Public key
@@ -57,6 +84,14 @@ pub enum VMError {
     NotEnoughFunds,
     /// Contract could not be found in the state
     UnknownContract,
+    /// Code could not be found in the state
+    UnknownCode,
+    /// A contract is already deployed at the given address
+    ContractAlreadyExists(ContractId),
+    /// The contract is not allowed to upgrade the code of another contract
+    UnauthorizedUpgrade,
+    /// Crediting the contract would overflow its balance
+    BalanceOverflow,
     /// WASM threw an error
     WASMError(failure::Error),
     /// wasmi trap triggered
@@ -69,6 +104,17 @@ pub enum VMError {
     InvalidWASMModule,
     /// Error propagated from underlying store
     StoreError(CanonError),
+    /// The event has more topics than allowed by the schedule
+    TooManyEventTopics,
+    /// The schedule does not fit in the registered schedule versions
+    InvalidSchedule,
+    /// A host call tried to mutate the state from within a query
+    StateMutationInQuery,
+    /// The maximum depth of nested contract calls was exceeded
+    CallDepthExceeded,
+    /// A contract was removed in favor of itself, which would burn its
+    /// balance
+    InvalidBeneficiary,
 }
 
 impl From<io::Error> for VMError {
@@ -104,6 +150,28 @@ impl VMError {
     }
 }
 
+impl VMError {
+    /// Returns the error raised by a host function, looking through the
+    /// wasmi traps wrapping it while unwinding nested contract calls
+    pub fn root_cause(&self) -> &VMError {
+        let host_error = match self {
+            VMError::Trap(trap)
+            | VMError::WasmiError(wasmi::Error::Trap(trap)) => {
+                match trap.kind() {
+                    TrapKind::Host(e) => e.downcast_ref::<VMError>(),
+                    _ => None,
+                }
+            }
+            VMError::WasmiError(wasmi::Error::Host(e)) => {
+                e.downcast_ref::<VMError>()
+            }
+            _ => None,
+        };
+
+        host_error.map_or(self, VMError::root_cause)
+    }
+}
+
 impl wasmi::HostError for VMError {}
 
 impl fmt::Display for VMError {
@@ -125,16 +193,42 @@ impl fmt::Display for VMError {
             VMError::NotEnoughFunds => write!(f, "Not enough funds error")?,
             VMError::WASMError(e) => write!(f, "WASM Error ({:?})", e)?,
             VMError::MemoryNotFound => write!(f, "Memory not found")?,
+            VMError::MemoryAccessOutOfBounds => {
+                write!(f, "Memory access out of bounds")?
+            }
             VMError::InvalidABICall => write!(f, "Invalid ABI Call")?,
+            VMError::InvalidImport {
+                module,
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Invalid import {}::{}, expected {}, found {}",
+                module, field, expected, found
+            )?,
             VMError::IOError(e) => write!(f, "Input/Output Error ({:?})", e)?,
             VMError::Trap(e) => write!(f, "Trap ({:?})", e)?,
             VMError::WasmiError(e) => write!(f, "WASMI Error ({:?})", e)?,
             VMError::UnknownContract => write!(f, "Unknown Contract")?,
+            VMError::UnknownCode => write!(f, "Unknown Code")?,
+            VMError::ContractAlreadyExists(id) => {
+                write!(f, "Contract already exists at {:?}", id)?
+            }
+            VMError::UnauthorizedUpgrade => write!(f, "Unauthorized upgrade")?,
+            VMError::BalanceOverflow => write!(f, "Balance overflow")?,
             VMError::InvalidWASMModule => write!(f, "Invalid WASM module")?,
             VMError::StoreError(e) => write!(f, "Store error {:?}", e)?,
             VMError::InstrumentalizationError(e) => {
                 write!(f, "Instrumentalization error {:?}", e)?
             }
+            VMError::TooManyEventTopics => write!(f, "Too many event topics")?,
+            VMError::InvalidSchedule => write!(f, "Invalid schedule")?,
+            VMError::StateMutationInQuery => {
+                write!(f, "State mutation attempted in a query")?
+            }
+            VMError::CallDepthExceeded => write!(f, "Call depth exceeded")?,
+            VMError::InvalidBeneficiary => write!(f, "Invalid beneficiary")?,
         }
         Ok(())
     }
@@ -186,6 +280,15 @@ pub struct Schedule {
     /// Gas cost per one byte written to the sandbox memory.
     pub sandbox_data_write_cost: Gas,
 
+    /// Base gas cost of a storage `get`, `put` or `hash`.
+    pub store_base_cost: Gas,
+
+    /// Gas cost per one byte put into storage.
+    pub store_put_per_byte_cost: Gas,
+
+    /// Gas cost per one byte hashed.
+    pub hash_per_byte_cost: Gas,
+
     /// The maximum number of topics supported by an event.
     pub max_event_topics: u32,
 
@@ -195,12 +298,38 @@ pub struct Schedule {
     /// how the stack frame cost is calculated.
     pub max_stack_height: u32,
 
+    /// Maximum depth of nested contract calls.
+    pub max_call_depth: u32,
+
+    /// Divisor of the gas kept back by a caller when calling into another
+    /// contract: a nested call can spend at most all but one
+    /// `call_gas_reserve_divisor`th of the gas left to its caller.
+    pub call_gas_reserve_divisor: Gas,
+
     /// Maximum number of memory pages allowed for a contract.
     pub max_memory_pages: u32,
 
     /// Maximum allowed size of a declared table.
     pub max_table_size: u32,
 
+    /// Maximum size of a contract's code, in bytes.
+    pub max_code_size: u32,
+
+    /// Maximum number of functions defined by a contract.
+    pub max_functions: u32,
+
+    /// Maximum number of locals declared by a function.
+    pub max_locals: u32,
+
+    /// Maximum number of globals defined by a contract.
+    pub max_globals: u32,
+
+    /// Maximum size of a data segment, in bytes.
+    pub max_data_segment_size: u32,
+
+    /// Maximum number of exports of a contract.
+    pub max_exports: u32,
+
     /// Whether the `ext_println` function is allowed to be used contracts.
     /// MUST only be enabled for `dev` chains, NOT for production chains
     pub enable_println: bool,
@@ -224,10 +353,21 @@ impl Default for Schedule {
             instantiate_base_cost: 175,
             sandbox_data_read_cost: 1,
             sandbox_data_write_cost: 1,
+            store_base_cost: 20,
+            store_put_per_byte_cost: 1,
+            hash_per_byte_cost: 1,
             max_event_topics: 4,
             max_stack_height: 64 * 1024,
+            max_call_depth: 32,
+            call_gas_reserve_divisor: 64,
             max_memory_pages: 16,
             max_table_size: 16 * 1024,
+            max_code_size: 512 * 1024,
+            max_functions: 4 * 1024,
+            max_locals: 1024,
+            max_globals: 256,
+            max_data_segment_size: 64 * 1024,
+            max_exports: 64,
             enable_println: false,
             max_subject_len: 32,
         }
@@ -0,0 +1,35 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Bounds-checked access to the memory of a contract.
+//!
+//! Offsets and lengths are provided by the contracts, so they must never be
+//! used to index the memory directly.
+
+use crate::VMError;
+
+/// Returns the `len` bytes of `mem` starting at `ofs`
+pub fn slice(mem: &[u8], ofs: usize, len: usize) -> Result<&[u8], VMError> {
+    ofs.checked_add(len)
+        .and_then(|end| mem.get(ofs..end))
+        .ok_or(VMError::MemoryAccessOutOfBounds)
+}
+
+/// Returns the `len` bytes of `mem` starting at `ofs`, mutably
+pub fn slice_mut(
+    mem: &mut [u8],
+    ofs: usize,
+    len: usize,
+) -> Result<&mut [u8], VMError> {
+    ofs.checked_add(len)
+        .and_then(move |end| mem.get_mut(ofs..end))
+        .ok_or(VMError::MemoryAccessOutOfBounds)
+}
+
+/// Returns the bytes of `mem` from `ofs` to its end
+pub fn slice_from(mem: &[u8], ofs: usize) -> Result<&[u8], VMError> {
+    mem.get(ofs..).ok_or(VMError::MemoryAccessOutOfBounds)
+}
@@ -0,0 +1,97 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::code::CodeHash;
+
+/// Identifies a parsed module by the hash of its code, the version of the
+/// [`Schedule`] and the id of the [`DeployPolicy`] the code was instrumented
+/// with
+///
+/// [`Schedule`]: crate::Schedule
+/// [`DeployPolicy`]: crate::DeployPolicy
+pub(crate) type ModuleKey = (CodeHash, u32, u8);
+
+/// A cache of parsed and validated modules, holding up to `capacity` of them.
+///
+/// When full, the least recently used module is evicted to make room for a
+/// new one.
+#[derive(Clone)]
+pub(crate) struct ModuleCache {
+    capacity: usize,
+    tick: u64,
+    modules: HashMap<ModuleKey, (Rc<wasmi::Module>, u64)>,
+}
+
+impl Default for ModuleCache {
+    fn default() -> Self {
+        ModuleCache::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl ModuleCache {
+    /// The number of modules cached by default
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Creates a cache holding up to `capacity` modules, a capacity of zero
+    /// disables caching
+    pub fn new(capacity: usize) -> Self {
+        ModuleCache {
+            capacity,
+            tick: 0,
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Returns the module cached under the given key, if any
+    pub fn get(&mut self, key: &ModuleKey) -> Option<Rc<wasmi::Module>> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        self.modules.get_mut(key).map(|(module, used)| {
+            *used = tick;
+            module.clone()
+        })
+    }
+
+    /// Caches the module under the given key, evicting the least recently
+    /// used module if the cache is full
+    pub fn insert(&mut self, key: ModuleKey, module: Rc<wasmi::Module>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if !self.modules.contains_key(&key)
+            && self.modules.len() >= self.capacity
+        {
+            self.evict();
+        }
+
+        self.tick += 1;
+        self.modules.insert(key, (module, self.tick));
+    }
+
+    /// Removes every module from the cache
+    pub fn clear(&mut self) {
+        self.modules.clear();
+    }
+
+    // A linear scan is fine here, since a miss costs parsing and validating a
+    // whole module anyway.
+    fn evict(&mut self) {
+        let lru = self
+            .modules
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(key, _)| *key);
+
+        if let Some(key) = lru {
+            self.modules.remove(&key);
+        }
+    }
+}
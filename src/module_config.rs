@@ -7,23 +7,156 @@
 use parity_wasm::elements;
 use wasmi_validation::{validate_module, PlainValidator};
 
+use crate::Schedule;
+
 pub use dusk_abi::{ContractId, ContractState};
 
-#[derive(Debug)]
+/// The reasons a contract can be rejected when instrumenting its code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InstrumentalizationError {
+    /// Gas metering could not be injected
     GasMeteringInjection,
+    /// The stack height limiter could not be injected
     StackHeightInjection,
+    /// The module declares more than one table
     MultipleTables,
+    /// The table is larger than allowed by the schedule
     MaxTableSize,
+    /// The memory is larger than allowed by the schedule
+    MaxMemoryPages,
+    /// The code is larger than allowed by the schedule
+    MaxCodeSize,
+    /// The module defines more functions than allowed by the schedule
+    MaxFunctions,
+    /// A function declares more locals than allowed by the schedule
+    MaxLocals,
+    /// The module defines more globals than allowed by the schedule
+    MaxGlobals,
+    /// A data segment is larger than allowed by the schedule
+    MaxDataSegmentSize,
+    /// The module has more exports than allowed by the schedule
+    MaxExports,
+    /// The code is not a valid WASM module
     InvalidByteCode,
 }
 
+/// The instrumentation applied to the contracts deployed on a
+/// [`NetworkState`].
+///
+/// Every step is enabled by default. Disabling any of them is only meant for
+/// development networks.
+///
+/// [`NetworkState`]: crate::NetworkState
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeployPolicy {
+    grow_cost: bool,
+    forbidden_floats: bool,
+    metering: bool,
+    table_size_limit: bool,
+    memory_limit: bool,
+    code_limits: bool,
+}
+
+impl Default for DeployPolicy {
+    fn default() -> Self {
+        DeployPolicy {
+            grow_cost: true,
+            forbidden_floats: true,
+            metering: true,
+            table_size_limit: true,
+            memory_limit: true,
+            code_limits: true,
+        }
+    }
+}
+
+impl DeployPolicy {
+    /// Whether growing the memory is charged for
+    pub fn with_grow_cost(mut self, enabled: bool) -> Self {
+        self.grow_cost = enabled;
+        self
+    }
+
+    /// Whether contracts using floating point instructions are rejected
+    pub fn with_forbidden_floats(mut self, enabled: bool) -> Self {
+        self.forbidden_floats = enabled;
+        self
+    }
+
+    /// Whether gas metering and the stack height limiter are injected
+    pub fn with_metering(mut self, enabled: bool) -> Self {
+        self.metering = enabled;
+        self
+    }
+
+    /// Whether the size of the table declared by contracts is limited
+    pub fn with_table_size_limit(mut self, enabled: bool) -> Self {
+        self.table_size_limit = enabled;
+        self
+    }
+
+    /// Whether the memory pages of contracts are limited
+    pub fn with_memory_limit(mut self, enabled: bool) -> Self {
+        self.memory_limit = enabled;
+        self
+    }
+
+    /// Whether the code size and complexity of contracts are limited
+    pub fn with_code_limits(mut self, enabled: bool) -> Self {
+        self.code_limits = enabled;
+        self
+    }
+
+    /// Returns an identifier of the policy, distinct for every combination
+    /// of enabled steps, under which instrumented code is stored and cached
+    pub fn id(&self) -> u8 {
+        [
+            self.grow_cost,
+            self.forbidden_floats,
+            self.metering,
+            self.table_size_limit,
+            self.memory_limit,
+            self.code_limits,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |id, (bit, enabled)| id | (*enabled as u8) << bit)
+    }
+
+    pub(crate) fn module_config(&self) -> ModuleConfig {
+        let mut config = ModuleConfig::new();
+
+        if self.grow_cost {
+            config.with_grow_cost();
+        }
+        if self.forbidden_floats {
+            config.with_forbidden_floats();
+        }
+        if self.metering {
+            config.with_metering();
+        }
+        if self.table_size_limit {
+            config.with_table_size_limit();
+        }
+        if self.memory_limit {
+            config.with_memory_limit();
+        }
+        if self.code_limits {
+            config.with_code_limits();
+        }
+
+        config
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct ModuleConfig {
     has_grow_cost: bool,
     has_forbidden_floats: bool,
     has_metering: bool,
     has_table_size_limit: bool,
+    has_memory_limit: bool,
+    has_code_limits: bool,
 }
 
 impl ModuleConfig {
@@ -33,6 +166,8 @@ impl ModuleConfig {
             has_forbidden_floats: false,
             has_metering: false,
             has_table_size_limit: false,
+            has_memory_limit: false,
+            has_code_limits: false,
         }
     }
 
@@ -56,14 +191,35 @@ impl ModuleConfig {
         self
     }
 
+    pub fn with_memory_limit(&mut self) -> &mut Self {
+        self.has_memory_limit = true;
+        self
+    }
+
+    pub fn with_code_limits(&mut self) -> &mut Self {
+        self.has_code_limits = true;
+        self
+    }
+
     pub fn apply(
         &self,
         code: &[u8],
+        schedule: &Schedule,
     ) -> Result<Vec<u8>, InstrumentalizationError> {
+        if self.has_code_limits && code.len() > schedule.max_code_size as usize
+        {
+            return Err(InstrumentalizationError::MaxCodeSize);
+        }
+
         let mut module = elements::deserialize_buffer(code)
             .or(Err(InstrumentalizationError::InvalidByteCode))?;
 
-        let schedule = crate::Schedule::default();
+        // The limits apply to the module as written, before the
+        // instrumentation adds to it
+        if self.has_code_limits {
+            check_code_limits(&module, schedule)?;
+        }
+
         let mut ruleset = pwasm_utils::rules::Set::new(
             schedule.regular_op_cost as u32,
             Default::default(),
@@ -108,6 +264,30 @@ impl ModuleConfig {
             }
         }
 
+        if self.has_memory_limit {
+            let max_pages = schedule.max_memory_pages;
+
+            if let Some(memory_section) = module.memory_section_mut() {
+                for memory in memory_section.entries_mut() {
+                    let initial = memory.limits().initial();
+                    let maximum = memory.limits().maximum();
+
+                    if initial > max_pages
+                        || maximum.map_or(false, |maximum| maximum > max_pages)
+                    {
+                        return Err(InstrumentalizationError::MaxMemoryPages);
+                    }
+
+                    // Declare the maximum, so that `memory.grow` fails past it
+                    // at runtime.
+                    if maximum.is_none() {
+                        *memory =
+                            elements::MemoryType::new(initial, Some(max_pages));
+                    }
+                }
+            }
+        }
+
         validate_module::<PlainValidator>(&module)
             .or(Err(InstrumentalizationError::InvalidByteCode))?;
 
@@ -116,3 +296,53 @@ impl ModuleConfig {
             .or(Err(InstrumentalizationError::InvalidByteCode))
     }
 }
+
+fn check_code_limits(
+    module: &elements::Module,
+    schedule: &Schedule,
+) -> Result<(), InstrumentalizationError> {
+    let functions = module
+        .function_section()
+        .map_or(0, |section| section.entries().len());
+
+    if functions > schedule.max_functions as usize {
+        return Err(InstrumentalizationError::MaxFunctions);
+    }
+
+    if let Some(code_section) = module.code_section() {
+        for body in code_section.bodies() {
+            let locals: u64 =
+                body.locals().iter().map(|local| local.count() as u64).sum();
+
+            if locals > schedule.max_locals as u64 {
+                return Err(InstrumentalizationError::MaxLocals);
+            }
+        }
+    }
+
+    let globals = module
+        .global_section()
+        .map_or(0, |section| section.entries().len());
+
+    if globals > schedule.max_globals as usize {
+        return Err(InstrumentalizationError::MaxGlobals);
+    }
+
+    if let Some(data_section) = module.data_section() {
+        for segment in data_section.entries() {
+            if segment.value().len() > schedule.max_data_segment_size as usize {
+                return Err(InstrumentalizationError::MaxDataSegmentSize);
+            }
+        }
+    }
+
+    let exports = module
+        .export_section()
+        .map_or(0, |section| section.entries().len());
+
+    if exports > schedule.max_exports as usize {
+        return Err(InstrumentalizationError::MaxExports);
+    }
+
+    Ok(())
+}
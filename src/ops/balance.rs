@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::call_context::CallContext;
+use crate::ops::AbiCall;
+use crate::VMError;
+
+use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
+
+/// Returns the native balance of the contract being executed
+pub struct Balance;
+
+impl AbiCall for Balance {
+    const ARGUMENTS: &'static [ValueType] = &[];
+    const RETURN: Option<ValueType> = Some(ValueType::I64);
+
+    fn call(
+        context: &mut CallContext,
+        _args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        let callee = *context.callee();
+        let balance = context.state().get_contract(&callee)?.balance();
+
+        Ok(Some(RuntimeValue::from(balance)))
+    }
+}
+
+/// Returns the value transferred along with the call being executed
+pub struct Value;
+
+impl AbiCall for Value {
+    const ARGUMENTS: &'static [ValueType] = &[];
+    const RETURN: Option<ValueType> = Some(ValueType::I64);
+
+    fn call(
+        context: &mut CallContext,
+        _args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        Ok(Some(RuntimeValue::from(context.value())))
+    }
+}
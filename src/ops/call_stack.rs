@@ -6,6 +6,7 @@
 
 use super::AbiCall;
 use crate::call_context::CallContext;
+use crate::memory;
 use crate::VMError;
 
 use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
@@ -24,13 +25,13 @@ impl AbiCall for Callee {
             let result_ofs = result_ofs as usize;
             let callee = *context.callee();
 
-            context
-                .memory_mut(|a| {
-                    a[result_ofs..result_ofs + 32]
-                        .copy_from_slice(callee.as_bytes());
-                    Ok(None)
-                })
-                .map_err(VMError::from_store_error)
+            context.charge_write(callee.as_bytes().len())?;
+
+            context.memory_mut(|a| {
+                memory::slice_mut(a, result_ofs, 32)?
+                    .copy_from_slice(callee.as_bytes());
+                Ok(None)
+            })
         } else {
             Err(VMError::InvalidArguments)
         }
@@ -51,13 +52,13 @@ impl AbiCall for Caller {
             let result_ofs = result_ofs as usize;
             let caller = *context.caller();
 
-            context
-                .memory_mut(|a| {
-                    a[result_ofs..result_ofs + 32]
-                        .copy_from_slice(caller.as_bytes());
-                    Ok(None)
-                })
-                .map_err(VMError::from_store_error)
+            context.charge_write(caller.as_bytes().len())?;
+
+            context.memory_mut(|a| {
+                memory::slice_mut(a, result_ofs, 32)?
+                    .copy_from_slice(caller.as_bytes());
+                Ok(None)
+            })
         } else {
             Err(VMError::InvalidArguments)
         }
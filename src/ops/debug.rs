@@ -6,6 +6,7 @@
 
 use super::AbiCall;
 use crate::call_context::CallContext;
+use crate::memory;
 use crate::VMError;
 
 use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
@@ -23,11 +24,13 @@ impl AbiCall for Debug {
         if let [RuntimeValue::I32(msg_ofs), RuntimeValue::I32(msg_len)] =
             *args.as_ref()
         {
-            context.memory(|a| {
-                let msg_ofs = msg_ofs as usize;
-                let msg_len = msg_len as usize;
+            let msg_ofs = msg_ofs as usize;
+            let msg_len = msg_len as usize;
+
+            context.charge_read(msg_len)?;
 
-                let slice = &a[msg_ofs..msg_ofs + msg_len];
+            context.memory(|a| {
+                let slice = memory::slice(a, msg_ofs, msg_len)?;
                 let str = std::str::from_utf8(slice)
                     .map_err(|_| VMError::InvalidUtf8)?;
                 println!("CONTRACT DEBUG: {:?}", str);
@@ -0,0 +1,144 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::call_context::CallContext;
+use crate::code::CodeHash;
+use crate::contract::Contract;
+use crate::memory;
+use crate::ops::AbiCall;
+use crate::VMError;
+
+use canonical::{Canon, Source};
+use dusk_abi::{ContractId, ContractState};
+use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
+
+/// Deploys a contract with the given code and initial state on behalf of the
+/// calling contract, writing the id of the new contract back to its memory
+pub struct Deploy;
+
+impl AbiCall for Deploy {
+    const ARGUMENTS: &'static [ValueType] = &[
+        ValueType::I32,
+        ValueType::I32,
+        ValueType::I32,
+        ValueType::I32,
+    ];
+    const RETURN: Option<ValueType> = None;
+
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(code_ofs), RuntimeValue::I32(code_len), RuntimeValue::I32(state_ofs), RuntimeValue::I32(ret_ofs)] =
+            *args.as_ref()
+        {
+            let code_ofs = code_ofs as usize;
+            let code_len = code_len as usize;
+            let state_ofs = state_ofs as usize;
+            let ret_ofs = ret_ofs as usize;
+
+            context.check_mutable()?;
+
+            context.charge(context.state().schedule().instantiate_base_cost)?;
+
+            let code = context.memory(|m| -> Result<_, VMError> {
+                Ok(memory::slice(m, code_ofs, code_len)?.to_vec())
+            })?;
+            let state = read_state(context, state_ofs)?;
+
+            context.charge_read(code_len + state.encoded_len())?;
+
+            // the code is charged by byte when stored
+            let deployer = *context.callee();
+            let (network, gas_meter) = context.state_and_gas_meter();
+            let id = network.deploy_by(
+                &deployer,
+                Contract::from_state(state, code),
+                gas_meter,
+            )?;
+
+            write_id(context, ret_ofs, &id)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
+
+/// Like [`Deploy`], but the contract runs code already stored in the state
+/// under the given hash
+pub struct DeployFromCodeHash;
+
+impl AbiCall for DeployFromCodeHash {
+    const ARGUMENTS: &'static [ValueType] =
+        &[ValueType::I32, ValueType::I32, ValueType::I32];
+    const RETURN: Option<ValueType> = None;
+
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(code_hash_ofs), RuntimeValue::I32(state_ofs), RuntimeValue::I32(ret_ofs)] =
+            *args.as_ref()
+        {
+            let code_hash_ofs = code_hash_ofs as usize;
+            let state_ofs = state_ofs as usize;
+            let ret_ofs = ret_ofs as usize;
+
+            context.check_mutable()?;
+            context.charge(context.state().schedule().instantiate_base_cost)?;
+
+            let mut code_hash = CodeHash::default();
+            context.memory(|m| -> Result<_, VMError> {
+                code_hash.copy_from_slice(memory::slice(
+                    m,
+                    code_hash_ofs,
+                    code_hash.len(),
+                )?);
+                Ok(())
+            })?;
+            let state = read_state(context, state_ofs)?;
+
+            context.charge_read(code_hash.len() + state.encoded_len())?;
+
+            context.state().get_code(&code_hash)?;
+
+            let deployer = *context.callee();
+            let (network, gas_meter) = context.state_and_gas_meter();
+            let id = network.deploy_by(
+                &deployer,
+                Contract::with_code_hash(state, code_hash),
+                gas_meter,
+            )?;
+
+            write_id(context, ret_ofs, &id)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
+
+fn read_state(
+    context: &CallContext,
+    state_ofs: usize,
+) -> Result<ContractState, VMError> {
+    context.memory(|m| {
+        let mut source = Source::new(memory::slice_from(m, state_ofs)?);
+        ContractState::decode(&mut source).map_err(VMError::from_store_error)
+    })
+}
+
+fn write_id(
+    context: &mut CallContext,
+    ret_ofs: usize,
+    id: &ContractId,
+) -> Result<Option<RuntimeValue>, VMError> {
+    context.charge_write(id.as_bytes().len())?;
+
+    context.memory_mut(|m| {
+        memory::slice_mut(m, ret_ofs, 32)?.copy_from_slice(id.as_bytes());
+        Ok(None)
+    })
+}
@@ -0,0 +1,85 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use super::AbiCall;
+use crate::call_context::CallContext;
+use crate::event::{Event, TOPIC_SIZE};
+use crate::memory;
+use crate::{Gas, VMError};
+
+use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
+
+pub struct Emit;
+
+impl AbiCall for Emit {
+    const ARGUMENTS: &'static [ValueType] = &[
+        ValueType::I32,
+        ValueType::I32,
+        ValueType::I32,
+        ValueType::I32,
+    ];
+    const RETURN: Option<ValueType> = None;
+
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(topics_ofs), RuntimeValue::I32(topics_len), RuntimeValue::I32(data_ofs), RuntimeValue::I32(data_len)] =
+            *args.as_ref()
+        {
+            let topics_ofs = topics_ofs as usize;
+            let topics_len = topics_len as usize;
+            let data_ofs = data_ofs as usize;
+            let data_len = data_len as usize;
+
+            context.check_mutable()?;
+
+            let schedule = context.state().schedule();
+
+            if topics_len > schedule.max_event_topics as usize {
+                return Err(VMError::TooManyEventTopics);
+            }
+
+            let cost = schedule
+                .event_base_cost
+                .saturating_add(
+                    schedule
+                        .event_per_topic_cost
+                        .saturating_mul(topics_len as Gas),
+                )
+                .saturating_add(
+                    schedule
+                        .event_data_per_byte_cost
+                        .saturating_mul(data_len as Gas),
+                );
+
+            context.charge(cost)?;
+
+            let (topics, data) = context.memory(|m| -> Result<_, VMError> {
+                let topics: Vec<[u8; TOPIC_SIZE]> =
+                    memory::slice(m, topics_ofs, topics_len * TOPIC_SIZE)?
+                        .chunks_exact(TOPIC_SIZE)
+                        .map(|chunk| {
+                            let mut topic = [0u8; TOPIC_SIZE];
+                            topic.copy_from_slice(chunk);
+                            topic
+                        })
+                        .collect();
+
+                let data = memory::slice(m, data_ofs, data_len)?.to_vec();
+
+                Ok((topics, data))
+            })?;
+
+            let contract = *context.callee();
+            context.emit(Event::new(contract, topics, data));
+
+            Ok(None)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
@@ -5,18 +5,25 @@
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
 use crate::call_context::CallContext;
+use crate::memory;
 use crate::VMError;
 
+use canonical::{Canon, Sink};
 use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
 
+pub mod balance;
 pub mod block_height;
 pub mod call_stack;
 pub mod debug;
+pub mod deploy;
+pub mod event;
 pub mod gas;
 pub mod panic;
 pub mod query;
+pub mod self_destruct;
 pub mod store;
 pub mod transact;
+pub mod upgrade;
 
 pub trait AbiCall {
     const ARGUMENTS: &'static [ValueType];
@@ -27,3 +34,39 @@ pub trait AbiCall {
         args: RuntimeArgs,
     ) -> Result<Option<RuntimeValue>, VMError>;
 }
+
+/// Status returned by `try_query` and `try_transact` when the call succeeded
+pub const CALL_SUCCEEDED: i32 = 0;
+/// Status returned by `try_query` and `try_transact` when the callee panicked
+pub const CALL_PANICKED: i32 = 1;
+/// Status returned by `try_query` and `try_transact` when the callee ran out
+/// of gas
+pub const CALL_OUT_OF_GAS: i32 = 2;
+/// Status returned by `try_query` and `try_transact` when the call failed for
+/// any other reason
+pub const CALL_FAILED: i32 = 3;
+
+/// Writes the message of a failed call at `ofs` in the caller's memory, and
+/// returns the matching status to be handed back to the caller
+fn write_failure(
+    context: &mut CallContext,
+    ofs: usize,
+    err: VMError,
+) -> Result<Option<RuntimeValue>, VMError> {
+    let (status, message) = match err.root_cause() {
+        VMError::ContractPanic(msg) => (CALL_PANICKED, msg.clone()),
+        VMError::OutOfGas => {
+            (CALL_OUT_OF_GAS, format!("{}", VMError::OutOfGas))
+        }
+        cause => (CALL_FAILED, format!("{}", cause)),
+    };
+
+    context.charge_write(message.encoded_len())?;
+
+    context.memory_mut(|m| {
+        let len = message.encoded_len();
+        let mut sink = Sink::new(memory::slice_mut(m, ofs, len)?);
+        message.encode(&mut sink);
+        Ok(Some(RuntimeValue::I32(status)))
+    })
+}
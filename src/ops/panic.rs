@@ -6,6 +6,7 @@
 
 use super::AbiCall;
 use crate::call_context::CallContext;
+use crate::memory;
 use crate::VMError;
 
 use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
@@ -27,15 +28,13 @@ impl AbiCall for Panic {
             let panic_len = panic_len as usize;
 
             context.memory(|a| {
-                Err(
-                    match String::from_utf8(
-                        a[panic_ofs..panic_ofs + panic_len].to_vec(),
-                    ) {
-                        Ok(panic_msg) => VMError::ContractPanic(panic_msg),
-                        Err(_) => VMError::InvalidUtf8,
-                    },
-                )
-            })?
+                let slice = memory::slice(a, panic_ofs, panic_len)?;
+
+                Err(match String::from_utf8(slice.to_vec()) {
+                    Ok(panic_msg) => VMError::ContractPanic(panic_msg),
+                    Err(_) => VMError::InvalidUtf8,
+                })
+            })
         } else {
             Err(VMError::InvalidArguments)
         }
@@ -5,11 +5,13 @@
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
 use crate::call_context::CallContext;
-use crate::ops::AbiCall;
+use crate::gas::Gas;
+use crate::memory;
+use crate::ops::{write_failure, AbiCall, CALL_SUCCEEDED};
 use crate::VMError;
 
 use canonical::{Canon, Sink, Source};
-use dusk_abi::{ContractId, Query};
+use dusk_abi::{ContractId, Query, ReturnValue};
 use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
 
 pub struct ExecuteQuery;
@@ -28,33 +30,94 @@ impl AbiCall for ExecuteQuery {
             let contract_id_ofs = contract_id_ofs as usize;
             let query_ofs = query_ofs as usize;
 
-            let (contract_id, query) = context
-                .memory(|m| {
-                    let contract_id = ContractId::from(
-                        &m[contract_id_ofs..contract_id_ofs + 32],
-                    );
+            let (contract_id, query) =
+                read_query(context, contract_id_ofs, query_ofs)?;
 
-                    let mut source = Source::new(&m[query_ofs..]);
-                    let query = Query::decode(&mut source)?;
+            let result = context.with_gas_limit(Gas::MAX, |context| {
+                context.query(contract_id, query)
+            })?;
 
-                    Ok((contract_id, query))
-                })
-                .map_err(VMError::from_store_error)?;
+            write_result(context, query_ofs, &result)?;
+            Ok(None)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
 
-            let result = context.query(contract_id, query)?;
+/// Like [`ExecuteQuery`], but a failing query is reported to the caller with
+/// a status code and an error message instead of trapping. The query can
+/// spend at most the given gas limit.
+pub struct TryQuery;
 
-            context
-                .memory_mut(|m| {
-                    // write back the return value
-                    let mut sink = Sink::new(&mut m[query_ofs..]);
-                    result.encode(&mut sink);
-                    Ok(())
-                })
-                .map_err(VMError::from_store_error)?;
+impl AbiCall for TryQuery {
+    const ARGUMENTS: &'static [ValueType] =
+        &[ValueType::I32, ValueType::I32, ValueType::I64];
+    const RETURN: Option<ValueType> = Some(ValueType::I32);
 
-            Ok(None)
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(contract_id_ofs), RuntimeValue::I32(query_ofs), RuntimeValue::I64(gas_limit)] =
+            *args.as_ref()
+        {
+            let contract_id_ofs = contract_id_ofs as usize;
+            let query_ofs = query_ofs as usize;
+
+            let (contract_id, query) =
+                read_query(context, contract_id_ofs, query_ofs)?;
+
+            let result = context.with_gas_limit(gas_limit as Gas, |context| {
+                context.query(contract_id, query)
+            });
+
+            match result {
+                Ok(result) => {
+                    write_result(context, query_ofs, &result)?;
+                    Ok(Some(RuntimeValue::I32(CALL_SUCCEEDED)))
+                }
+                Err(err) => write_failure(context, query_ofs, err),
+            }
         } else {
             Err(VMError::InvalidArguments)
         }
     }
 }
+
+fn read_query(
+    context: &mut CallContext,
+    contract_id_ofs: usize,
+    query_ofs: usize,
+) -> Result<(ContractId, Query), VMError> {
+    let (contract_id, query) = context.memory(|m| -> Result<_, VMError> {
+        let contract_id =
+            ContractId::from(memory::slice(m, contract_id_ofs, 32)?);
+
+        let mut source = Source::new(memory::slice_from(m, query_ofs)?);
+        let query =
+            Query::decode(&mut source).map_err(VMError::from_store_error)?;
+
+        Ok((contract_id, query))
+    })?;
+
+    context.charge_read(contract_id.as_bytes().len() + query.encoded_len())?;
+
+    Ok((contract_id, query))
+}
+
+fn write_result(
+    context: &mut CallContext,
+    query_ofs: usize,
+    result: &ReturnValue,
+) -> Result<(), VMError> {
+    context.charge_write(result.encoded_len())?;
+
+    context.memory_mut(|m| {
+        // write back the return value
+        let len = result.encoded_len();
+        let mut sink = Sink::new(memory::slice_mut(m, query_ofs, len)?);
+        result.encode(&mut sink);
+        Ok(())
+    })
+}
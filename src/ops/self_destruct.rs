@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::call_context::CallContext;
+use crate::memory;
+use crate::ops::AbiCall;
+use crate::VMError;
+
+use dusk_abi::ContractId;
+use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
+
+/// Removes the calling contract from the state once the transaction is
+/// successfully committed, crediting its balance to the beneficiary
+pub struct SelfDestruct;
+
+impl AbiCall for SelfDestruct {
+    const ARGUMENTS: &'static [ValueType] = &[ValueType::I32];
+    const RETURN: Option<ValueType> = None;
+
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(beneficiary_ofs)] = *args.as_ref() {
+            let beneficiary_ofs = beneficiary_ofs as usize;
+
+            let beneficiary = context.memory(|m| -> Result<_, VMError> {
+                Ok(ContractId::from(memory::slice(m, beneficiary_ofs, 32)?))
+            })?;
+
+            context.charge_read(beneficiary.as_bytes().len())?;
+            context.self_destruct(beneficiary)?;
+
+            Ok(None)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
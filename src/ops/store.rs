@@ -5,6 +5,8 @@
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
 use crate::call_context::CallContext;
+use crate::gas::Gas;
+use crate::memory;
 use crate::ops::AbiCall;
 use crate::VMError;
 
@@ -29,21 +31,26 @@ impl AbiCall for Get {
             let write_buf = write_buf as usize;
             let write_len = write_len as usize;
 
-            context
-                .memory_mut(|mem| {
-                    let mut source = Source::new(&mem[hash_ofs..]);
-                    let hash = IdHash::decode(&mut source)?;
-
-                    // we don't allow get requests to fail in the bridge
-                    // communication since that is the
-                    // responsibility of the host.
-                    Store::get(
-                        &hash,
-                        &mut mem[write_buf..write_buf + write_len],
-                    )?;
-                    Ok(None)
-                })
-                .map_err(VMError::from_store_error)
+            context.charge(context.state().schedule().store_base_cost)?;
+            context.charge_read(core::mem::size_of::<IdHash>())?;
+            context.charge_write(write_len)?;
+
+            context.memory_mut(|mem| {
+                let mut source =
+                    Source::new(memory::slice_from(mem, hash_ofs)?);
+                let hash = IdHash::decode(&mut source)
+                    .map_err(VMError::from_store_error)?;
+
+                // we don't allow get requests to fail in the bridge
+                // communication since that is the
+                // responsibility of the host.
+                Store::get(
+                    &hash,
+                    memory::slice_mut(mem, write_buf, write_len)?,
+                )
+                .map_err(VMError::from_store_error)?;
+                Ok(None)
+            })
         } else {
             Err(VMError::InvalidArguments)
         }
@@ -68,18 +75,31 @@ impl AbiCall for Put {
             let len = len as usize;
             let ret = ret as usize;
 
-            context
-                .memory_mut(|mem| {
-                    // only non-inlined values end up written here
-                    debug_assert!(len > core::mem::size_of::<IdHash>());
-                    let hash = Store::put(&mem[ofs..ofs + len]);
+            // only non-inlined values are put, shorter values are rejected
+            // rather than trusted
+            if len <= core::mem::size_of::<IdHash>() {
+                return Err(VMError::InvalidArguments);
+            }
 
-                    let mut sink = Sink::new(&mut mem[ret..]);
-                    hash.encode(&mut sink);
+            let schedule = context.state().schedule();
+            let put_cost = schedule.store_base_cost.saturating_add(
+                schedule.store_put_per_byte_cost.saturating_mul(len as Gas),
+            );
 
-                    Ok(None)
-                })
-                .map_err(VMError::from_store_error)
+            context.charge(put_cost)?;
+            context.charge_read(len)?;
+            charge_hash(context, len)?;
+            context.charge_write(core::mem::size_of::<IdHash>())?;
+
+            context.memory_mut(|mem| {
+                let hash = Store::put(memory::slice(mem, ofs, len)?);
+
+                let mut sink =
+                    Sink::new(memory::slice_mut(mem, ret, hash.encoded_len())?);
+                hash.encode(&mut sink);
+
+                Ok(None)
+            })
         } else {
             Err(VMError::InvalidArguments)
         }
@@ -104,17 +124,26 @@ impl AbiCall for Hash {
             let len = len as usize;
             let ret = ret as usize;
 
-            context
-                .memory_mut(|mem| {
-                    let hash = Store::hash(&mem[ofs..ofs + len]);
+            context.charge(context.state().schedule().store_base_cost)?;
+            context.charge_read(len)?;
+            charge_hash(context, len)?;
+            context.charge_write(core::mem::size_of::<IdHash>())?;
 
-                    // write id into wasm memory
-                    mem[ret..ret + hash.len()].copy_from_slice(&hash);
-                    Ok(None)
-                })
-                .map_err(VMError::from_store_error)
+            context.memory_mut(|mem| {
+                let hash = Store::hash(memory::slice(mem, ofs, len)?);
+
+                // write id into wasm memory
+                memory::slice_mut(mem, ret, hash.len())?.copy_from_slice(&hash);
+                Ok(None)
+            })
         } else {
             Err(VMError::InvalidArguments)
         }
     }
 }
+
+/// Charges for hashing `len` bytes
+fn charge_hash(context: &mut CallContext, len: usize) -> Result<(), VMError> {
+    let cost = context.state().schedule().hash_per_byte_cost;
+    context.charge(cost.saturating_mul(len as Gas))
+}
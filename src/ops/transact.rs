@@ -5,11 +5,13 @@
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
 use crate::call_context::CallContext;
-use crate::ops::AbiCall;
+use crate::gas::Gas;
+use crate::memory;
+use crate::ops::{write_failure, AbiCall, CALL_SUCCEEDED};
 use crate::VMError;
 
 use canonical::{Canon, Sink, Source};
-use dusk_abi::{ContractId, ContractState, Transaction};
+use dusk_abi::{ContractId, ContractState, ReturnValue, Transaction};
 use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
 
 pub struct ApplyTransaction;
@@ -28,37 +30,155 @@ impl AbiCall for ApplyTransaction {
             let contract_id_ofs = contract_id_ofs as usize;
             let transaction_ofs = transaction_ofs as usize;
 
-            let (contract_id, state, transaction) = context
-                .memory(|m| {
-                    let contract_id = ContractId::from(
-                        &m[contract_id_ofs..contract_id_ofs + 32],
-                    );
+            let (contract_id, transaction) =
+                read_transaction(context, contract_id_ofs, transaction_ofs)?;
 
-                    let mut source = Source::new(&m[transaction_ofs..]);
+            let (state, result) = context
+                .with_gas_limit(Gas::MAX, |context| {
+                    context.transact(contract_id, transaction)
+                })?;
 
-                    let state = ContractState::decode(&mut source)?;
-                    let transaction = Transaction::decode(&mut source)?;
+            write_result(context, transaction_ofs, &state, &result)?;
+            Ok(None)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
 
-                    Ok((contract_id, state, transaction))
-                })
-                .map_err(VMError::from_store_error)?;
+/// Like [`ApplyTransaction`], moving the given value from the balance of the
+/// caller to the balance of the callee along with the transaction
+pub struct TransactWithValue;
+
+impl AbiCall for TransactWithValue {
+    const ARGUMENTS: &'static [ValueType] =
+        &[ValueType::I32, ValueType::I32, ValueType::I64];
+    const RETURN: Option<ValueType> = None;
+
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(contract_id_ofs), RuntimeValue::I32(transaction_ofs), RuntimeValue::I64(value)] =
+            *args.as_ref()
+        {
+            let contract_id_ofs = contract_id_ofs as usize;
+            let transaction_ofs = transaction_ofs as usize;
+
+            let (contract_id, transaction) =
+                read_transaction(context, contract_id_ofs, transaction_ofs)?;
+
+            let sender = *context.callee();
+            let (state, result) =
+                context.with_gas_limit(Gas::MAX, |context| {
+                    context.transact_with_value(
+                        sender,
+                        contract_id,
+                        transaction,
+                        value as u64,
+                    )
+                })?;
+
+            write_result(context, transaction_ofs, &state, &result)?;
+            Ok(None)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
 
-            let callee = *context.callee();
-            *context.state_mut().get_contract_mut(&callee)?.state_mut() = state;
+/// Like [`ApplyTransaction`], but a failing transaction is reported to the
+/// caller with a status code and an error message instead of trapping. Any
+/// state change made by the failed transaction is reverted. The transaction
+/// can spend at most the given gas limit.
+pub struct TryTransaction;
 
-            let (state, result) = context.transact(contract_id, transaction)?;
+impl AbiCall for TryTransaction {
+    const ARGUMENTS: &'static [ValueType] =
+        &[ValueType::I32, ValueType::I32, ValueType::I64];
+    const RETURN: Option<ValueType> = Some(ValueType::I32);
 
-            context
-                .memory_mut(|m| {
-                    // write back the return value
-                    let mut sink = Sink::new(&mut m[transaction_ofs..]);
-                    state.encode(&mut sink);
-                    result.encode(&mut sink);
-                    Ok(None)
-                })
-                .map_err(VMError::from_store_error)
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(contract_id_ofs), RuntimeValue::I32(transaction_ofs), RuntimeValue::I64(gas_limit)] =
+            *args.as_ref()
+        {
+            let contract_id_ofs = contract_id_ofs as usize;
+            let transaction_ofs = transaction_ofs as usize;
+
+            let (contract_id, transaction) =
+                read_transaction(context, contract_id_ofs, transaction_ofs)?;
+
+            let result = context.with_gas_limit(gas_limit as Gas, |context| {
+                context.transact(contract_id, transaction)
+            });
+
+            match result {
+                Ok((state, result)) => {
+                    write_result(context, transaction_ofs, &state, &result)?;
+                    Ok(Some(RuntimeValue::I32(CALL_SUCCEEDED)))
+                }
+                Err(err) => write_failure(context, transaction_ofs, err),
+            }
         } else {
             Err(VMError::InvalidArguments)
         }
     }
 }
+
+/// Reads the target and transaction from the caller's memory, and syncs the
+/// caller's state to the network state so the callee can observe it
+fn read_transaction(
+    context: &mut CallContext,
+    contract_id_ofs: usize,
+    transaction_ofs: usize,
+) -> Result<(ContractId, Transaction), VMError> {
+    context.check_mutable()?;
+
+    let (contract_id, state, transaction) =
+        context.memory(|m| -> Result<_, VMError> {
+            let contract_id =
+                ContractId::from(memory::slice(m, contract_id_ofs, 32)?);
+
+            let mut source =
+                Source::new(memory::slice_from(m, transaction_ofs)?);
+
+            let state = ContractState::decode(&mut source)
+                .map_err(VMError::from_store_error)?;
+            let transaction = Transaction::decode(&mut source)
+                .map_err(VMError::from_store_error)?;
+
+            Ok((contract_id, state, transaction))
+        })?;
+
+    context.charge_read(
+        contract_id.as_bytes().len()
+            + state.encoded_len()
+            + transaction.encoded_len(),
+    )?;
+
+    let callee = *context.callee();
+    *context.state_mut().get_contract_mut(&callee)?.state_mut() = state;
+
+    Ok((contract_id, transaction))
+}
+
+fn write_result(
+    context: &mut CallContext,
+    transaction_ofs: usize,
+    state: &ContractState,
+    result: &ReturnValue,
+) -> Result<(), VMError> {
+    context.charge_write(state.encoded_len() + result.encoded_len())?;
+
+    context.memory_mut(|m| {
+        // write back the return value
+        let len = state.encoded_len() + result.encoded_len();
+        let mut sink = Sink::new(memory::slice_mut(m, transaction_ofs, len)?);
+        state.encode(&mut sink);
+        result.encode(&mut sink);
+        Ok(())
+    })
+}
@@ -0,0 +1,62 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::call_context::CallContext;
+use crate::code::CodeHash;
+use crate::memory;
+use crate::ops::AbiCall;
+use crate::VMError;
+
+use dusk_abi::ContractId;
+use wasmi::{RuntimeArgs, RuntimeValue, ValueType};
+
+/// Replaces the code of the target contract with code already stored in the
+/// state, and migrates its state. Only the contract itself or its admin are
+/// allowed to upgrade it.
+pub struct Upgrade;
+
+impl AbiCall for Upgrade {
+    const ARGUMENTS: &'static [ValueType] = &[ValueType::I32, ValueType::I32];
+    const RETURN: Option<ValueType> = None;
+
+    fn call(
+        context: &mut CallContext,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, VMError> {
+        if let [RuntimeValue::I32(contract_id_ofs), RuntimeValue::I32(code_hash_ofs)] =
+            *args.as_ref()
+        {
+            let contract_id_ofs = contract_id_ofs as usize;
+            let code_hash_ofs = code_hash_ofs as usize;
+
+            context.check_mutable()?;
+
+            let mut code_hash = CodeHash::default();
+            let contract_id = context.memory(|m| -> Result<_, VMError> {
+                code_hash.copy_from_slice(memory::slice(
+                    m,
+                    code_hash_ofs,
+                    code_hash.len(),
+                )?);
+                Ok(ContractId::from(memory::slice(m, contract_id_ofs, 32)?))
+            })?;
+
+            context
+                .charge_read(contract_id.as_bytes().len() + code_hash.len())?;
+
+            let caller = *context.callee();
+            context
+                .state_mut()
+                .upgrade_by(&caller, &contract_id, code_hash)?;
+
+            context.migrate(contract_id)?;
+
+            Ok(None)
+        } else {
+            Err(VMError::InvalidArguments)
+        }
+    }
+}
@@ -0,0 +1,188 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::contract::ContractId;
+use crate::event::Event;
+use crate::gas::Gas;
+use crate::VMError;
+
+/// The kind of a contract call
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallKind {
+    /// A read-only query
+    Query,
+    /// A state changing transaction
+    Transaction,
+    /// The migration of a contract state, after its code was upgraded
+    Migration,
+    /// The initialization of a contract state, when it is deployed
+    Init,
+}
+
+/// A contract call performed during a transaction, along with the calls it
+/// performed in turn
+#[derive(Clone, Debug, PartialEq)]
+pub struct Call {
+    pub(crate) contract: ContractId,
+    pub(crate) kind: CallKind,
+    pub(crate) gas_spent: Gas,
+    pub(crate) calls: Vec<Call>,
+}
+
+impl Call {
+    pub(crate) fn new(contract: ContractId, kind: CallKind) -> Self {
+        Call {
+            contract,
+            kind,
+            gas_spent: 0,
+            calls: vec![],
+        }
+    }
+
+    /// Returns the id of the called contract
+    pub fn contract(&self) -> &ContractId {
+        &self.contract
+    }
+
+    /// Returns the kind of the call
+    pub fn kind(&self) -> CallKind {
+        self.kind
+    }
+
+    /// Returns the gas spent by the call, including its nested calls
+    pub fn gas_spent(&self) -> Gas {
+        self.gas_spent
+    }
+
+    /// Returns the calls performed by the called contract
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+}
+
+/// The final status of a transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// The transaction succeeded and its changes were applied
+    Success,
+    /// A contract panicked with the given message
+    Panic(String),
+    /// The transaction ran out of gas
+    OutOfGas,
+    /// The transaction failed with the given error
+    Error(String),
+}
+
+impl From<VMError> for TransactionStatus {
+    fn from(err: VMError) -> Self {
+        match err.root_cause() {
+            VMError::ContractPanic(msg) => {
+                TransactionStatus::Panic(msg.clone())
+            }
+            VMError::OutOfGas => TransactionStatus::OutOfGas,
+            cause => TransactionStatus::Error(cause.to_string()),
+        }
+    }
+}
+
+/// The outcome of a transaction, as returned by
+/// [`NetworkState::transact_with_receipt`]
+///
+/// [`NetworkState::transact_with_receipt`]:
+/// crate::NetworkState::transact_with_receipt
+#[derive(Clone, Debug, PartialEq)]
+pub struct Receipt<R> {
+    pub(crate) status: TransactionStatus,
+    pub(crate) ret: Option<R>,
+    pub(crate) gas_spent: Gas,
+    pub(crate) events: Vec<Event>,
+    pub(crate) calls: Vec<Call>,
+}
+
+impl<R> Receipt<R> {
+    /// Returns the final status of the transaction
+    pub fn status(&self) -> &TransactionStatus {
+        &self.status
+    }
+
+    /// Returns `true` if the transaction succeeded
+    pub fn is_success(&self) -> bool {
+        self.status == TransactionStatus::Success
+    }
+
+    /// Returns the decoded return value, if the transaction succeeded
+    pub fn ret(&self) -> Option<&R> {
+        self.ret.as_ref()
+    }
+
+    /// Consumes the receipt, returning the decoded return value if the
+    /// transaction succeeded
+    pub fn into_ret(self) -> Option<R> {
+        self.ret
+    }
+
+    /// Returns the gas spent by the transaction
+    pub fn gas_spent(&self) -> Gas {
+        self.gas_spent
+    }
+
+    /// Returns the events emitted by the transaction. Failed transactions do
+    /// not emit any event.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Returns the tree of contract calls performed by the transaction
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+}
+
+/// The outcome of a transaction dry-run, as returned by
+/// [`NetworkState::estimate_gas`]
+///
+/// [`NetworkState::estimate_gas`]: crate::NetworkState::estimate_gas
+#[derive(Debug)]
+pub struct GasEstimate<R> {
+    pub(crate) gas_spent: Gas,
+    pub(crate) required_limit: Gas,
+    pub(crate) peak_nested_gas: Gas,
+    pub(crate) result: Result<R, VMError>,
+}
+
+impl<R> GasEstimate<R> {
+    /// Returns the gas the transaction would spend
+    pub fn gas_spent(&self) -> Gas {
+        self.gas_spent
+    }
+
+    /// Returns the lowest limit a [`GasMeter`] needs for the transaction to
+    /// succeed. It is higher than the gas spent, since the meter runs out of
+    /// gas as soon as its budget is exhausted, and since callers keep a
+    /// reserve of gas back from the calls they nest.
+    ///
+    /// [`GasMeter`]: crate::GasMeter
+    pub fn required_limit(&self) -> Gas {
+        self.required_limit
+    }
+
+    /// Returns the highest gas spent by a single call nested in the
+    /// transaction, or zero if the transaction performs no nested call
+    pub fn peak_nested_gas(&self) -> Gas {
+        self.peak_nested_gas
+    }
+
+    /// Returns the result the transaction would produce
+    pub fn result(&self) -> Result<&R, &VMError> {
+        self.result.as_ref()
+    }
+
+    /// Consumes the estimate, returning the result the transaction would
+    /// produce
+    pub fn into_result(self) -> Result<R, VMError> {
+        self.result
+    }
+}
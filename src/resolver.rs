@@ -8,9 +8,10 @@ use crate::call_context::Resolver;
 use crate::ops::*;
 use crate::VMError;
 
+use parity_wasm::elements::{self, External, Type};
 use wasmi::{
     self, FuncInstance, FuncRef, ModuleImportResolver, RuntimeArgs,
-    RuntimeValue, Signature,
+    RuntimeValue, Signature, ValueType,
 };
 
 use crate::call_context::{CallContext, Invoke};
@@ -22,24 +23,56 @@ macro_rules! abi_resolver {
         #[derive(Clone, Default)]
         $visibility struct $name;
 
+        impl $name {
+            /// Returns the signature of the host function with the given
+            /// name, if it's part of the ABI
+            pub fn signature(field_name: &str) -> Option<Signature> {
+                match field_name {
+                    $(
+                        $op_name => Some(Signature::new(
+                            <$op as AbiCall>::ARGUMENTS,
+                            <$op as AbiCall>::RETURN,
+                        ))
+                    ),*
+
+                    ,
+
+                    _ => None
+                }
+            }
+        }
+
         impl ModuleImportResolver for $name {
-            fn resolve_func(&self, field_name: &str, _signature: &Signature) -> Result<FuncRef, wasmi::Error>
+            fn resolve_func(&self, field_name: &str, signature: &Signature) -> Result<FuncRef, wasmi::Error>
             where $(
                 $op : AbiCall,
                 )*
             {
                 match field_name {
                     $(
-                        $op_name => Ok(FuncInstance::alloc_host(
-                            Signature::new(<$op as AbiCall>::ARGUMENTS,
-                                           <$op as AbiCall>::RETURN),
-                            $id,
-                        ))
+                        $op_name => {
+                            let expected = Signature::new(
+                                <$op as AbiCall>::ARGUMENTS,
+                                <$op as AbiCall>::RETURN,
+                            );
+
+                            if signature != &expected {
+                                return Err(wasmi::Error::Instantiation(format!(
+                                    "invalid signature for {:?}",
+                                    field_name
+                                )));
+                            }
+
+                            Ok(FuncInstance::alloc_host(expected, $id))
+                        }
                     ),*
 
                     ,
 
-                    _ => panic!("invalid function name {:?}", field_name)
+                    _ => Err(wasmi::Error::Instantiation(format!(
+                        "invalid function name {:?}",
+                        field_name
+                    )))
                 }
             }
         }
@@ -57,7 +90,7 @@ macro_rules! abi_resolver {
 
                     ,
 
-                    _ => panic!("invalid index {:?}", index)
+                    _ => Err(VMError::InvalidABICall)
                 }
             }
         }
@@ -80,6 +113,115 @@ abi_resolver! {
         11, "gas" => gas::Gas,
         12, "gas_consumed" => gas::GasConsumed,
         13, "gas_left" => gas::GasLeft,
-        14, "block_height" => block_height::BlockHeight
+        14, "block_height" => block_height::BlockHeight,
+        15, "emit" => event::Emit,
+        16, "try_query" => query::TryQuery,
+        17, "try_transact" => transact::TryTransaction,
+        18, "deploy" => deploy::Deploy,
+        19, "deploy_from_code_hash" => deploy::DeployFromCodeHash,
+        20, "upgrade" => upgrade::Upgrade,
+        21, "self_destruct" => self_destruct::SelfDestruct,
+        22, "balance" => balance::Balance,
+        23, "value" => balance::Value,
+        24, "transact_with_value" => transact::TransactWithValue
+    }
+}
+
+/// The module names contracts can import host functions from
+const ABI_MODULES: &[&str] = &["env", "canon"];
+
+/// Checks that every import of the given module is a host function of the
+/// ABI, with the expected signature
+pub(crate) fn validate_imports(code: &[u8]) -> Result<(), VMError> {
+    let module = elements::deserialize_buffer::<elements::Module>(code)
+        .or(Err(VMError::InvalidWASMModule))?;
+
+    let imports = match module.import_section() {
+        Some(section) => section.entries(),
+        None => return Ok(()),
+    };
+
+    let types = module
+        .type_section()
+        .map(|section| section.types())
+        .unwrap_or_default();
+
+    for import in imports {
+        let invalid_import =
+            |expected: &str, found: &str| VMError::InvalidImport {
+                module: import.module().into(),
+                field: import.field().into(),
+                expected: expected.into(),
+                found: found.into(),
+            };
+
+        if !ABI_MODULES.contains(&import.module()) {
+            return Err(invalid_import(
+                &ABI_MODULES.join(" or "),
+                import.module(),
+            ));
+        }
+
+        let type_ref = match import.external() {
+            External::Function(type_ref) => *type_ref as usize,
+            External::Table(_) => {
+                return Err(invalid_import("function", "table"))
+            }
+            External::Memory(_) => {
+                return Err(invalid_import("function", "memory"))
+            }
+            External::Global(_) => {
+                return Err(invalid_import("function", "global"))
+            }
+        };
+
+        let expected =
+            CompoundResolver::signature(import.field()).ok_or_else(|| {
+                invalid_import("a function of the ABI", import.field())
+            })?;
+
+        let found = match types.get(type_ref) {
+            Some(Type::Function(func)) => {
+                let params = func
+                    .params()
+                    .iter()
+                    .map(|ty| value_type(*ty))
+                    .collect::<Vec<_>>();
+                Signature::new(params, func.return_type().map(value_type))
+            }
+            None => return Err(VMError::InvalidWASMModule),
+        };
+
+        if found != expected {
+            return Err(invalid_import(
+                &describe_signature(&expected),
+                &describe_signature(&found),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn value_type(ty: elements::ValueType) -> ValueType {
+    match ty {
+        elements::ValueType::I32 => ValueType::I32,
+        elements::ValueType::I64 => ValueType::I64,
+        elements::ValueType::F32 => ValueType::F32,
+        elements::ValueType::F64 => ValueType::F64,
+    }
+}
+
+fn describe_signature(signature: &Signature) -> String {
+    let params = signature
+        .params()
+        .iter()
+        .map(|ty| format!("{:?}", ty))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match signature.return_type() {
+        Some(ret) => format!("fn({}) -> {:?}", params, ret),
+        None => format!("fn({})", params),
     }
 }
@@ -0,0 +1,87 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound::{Excluded, Unbounded};
+
+use crate::{Schedule, VMError};
+
+/// The [`Schedule`]s of the network, indexed by the block height at which
+/// they become active.
+///
+/// A schedule is always registered at genesis, and schedule versions are
+/// strictly increasing with the activation height. A version identifies a
+/// single set of costs, so it is never registered twice, even once the
+/// schedule it was registered with has been replaced.
+#[derive(Clone)]
+pub(crate) struct ScheduleRegistry {
+    schedules: BTreeMap<u64, Schedule>,
+    versions: BTreeSet<u32>,
+}
+
+impl Default for ScheduleRegistry {
+    fn default() -> Self {
+        ScheduleRegistry::new(Schedule::default())
+    }
+}
+
+impl ScheduleRegistry {
+    /// Creates a registry with the given schedule active from genesis
+    pub fn new(genesis: Schedule) -> Self {
+        let mut versions = BTreeSet::new();
+        versions.insert(genesis.version);
+
+        let mut schedules = BTreeMap::new();
+        schedules.insert(0, genesis);
+
+        ScheduleRegistry {
+            schedules,
+            versions,
+        }
+    }
+
+    /// Registers a schedule active from `height` onwards, replacing any
+    /// schedule registered at the same height.
+    ///
+    /// The version of the schedule must not have been registered before.
+    pub fn register(
+        &mut self,
+        height: u64,
+        schedule: Schedule,
+    ) -> Result<(), VMError> {
+        if self.versions.contains(&schedule.version) {
+            return Err(VMError::InvalidSchedule);
+        }
+
+        let previous = self.schedules.range(..height).next_back();
+        let next = self.schedules.range((Excluded(height), Unbounded)).next();
+
+        if let Some((_, previous)) = previous {
+            if previous.version >= schedule.version {
+                return Err(VMError::InvalidSchedule);
+            }
+        }
+
+        if let Some((_, next)) = next {
+            if next.version <= schedule.version {
+                return Err(VMError::InvalidSchedule);
+            }
+        }
+
+        self.versions.insert(schedule.version);
+        self.schedules.insert(height, schedule);
+        Ok(())
+    }
+
+    /// Returns the schedule active at the given block height
+    pub fn at(&self, height: u64) -> &Schedule {
+        self.schedules
+            .range(..=height)
+            .next_back()
+            .map(|(_, schedule)| schedule)
+            .expect("A schedule is always registered at genesis")
+    }
+}
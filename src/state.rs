@@ -10,7 +10,7 @@ use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 
 use canonical::{Canon, CanonError, Sink, Source, Store};
-use dusk_abi::{HostModule, Query, Transaction};
+use dusk_abi::{ContractState, HostModule, Query, Transaction};
 use dusk_hamt::Hamt;
 #[cfg(feature = "persistence")]
 use microkelvin::{
@@ -18,90 +18,600 @@ use microkelvin::{
 };
 
 use crate::call_context::CallContext;
+use crate::code::{Code, CodeHash};
 use crate::contract::{Contract, ContractId};
-use crate::gas::GasMeter;
-use crate::VMError;
+use crate::event::Event;
+use crate::gas::{Gas, GasMeter};
+use crate::module_cache::ModuleCache;
+use crate::module_config::DeployPolicy;
+use crate::receipt::{Call, GasEstimate, Receipt, TransactionStatus};
+use crate::resolver;
+use crate::schedule::ScheduleRegistry;
+use crate::{Schedule, VMError};
 
 type BoxedHostModule = Box<dyn HostModule>;
 
+/// The parts of the state modified since a checkpoint began, with the value
+/// they had at that point
+#[derive(Clone, Default)]
+struct Checkpoint {
+    nonce: u64,
+    code_count: u64,
+    /// `None` means the contract did not exist
+    contracts: HashMap<ContractId, Option<Contract>>,
+    /// `None` means the code was not stored
+    codes: HashMap<CodeHash, Option<Code>>,
+}
+
+/// Identifies a persisted [`NetworkState`], as returned by
+/// [`NetworkState::persist`].
+///
+/// Nodes keep track of a persisted state by storing the parts of its id, and
+/// rebuild it with [`NetworkStateId::new`].
+#[cfg(feature = "persistence")]
+#[derive(Debug, Clone)]
+pub struct NetworkStateId {
+    nonce: u64,
+    code_count: u64,
+    contracts: PersistedId,
+    codes: PersistedId,
+    code_index: PersistedId,
+}
+
+#[cfg(feature = "persistence")]
+impl NetworkStateId {
+    /// Creates an id from its parts, e.g. as read back by a node which stored
+    /// them
+    pub fn new(
+        nonce: u64,
+        code_count: u64,
+        contracts: PersistedId,
+        codes: PersistedId,
+        code_index: PersistedId,
+    ) -> Self {
+        NetworkStateId {
+            nonce,
+            code_count,
+            contracts,
+            codes,
+            code_index,
+        }
+    }
+
+    /// Returns the number of contracts deployed by
+    /// [`NetworkState::DEPLOYER`]
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Returns the number of codes ever stored
+    pub fn code_count(&self) -> u64 {
+        self.code_count
+    }
+
+    /// Returns the id of the persisted contracts
+    pub fn contracts(&self) -> &PersistedId {
+        &self.contracts
+    }
+
+    /// Returns the id of the persisted codes
+    pub fn codes(&self) -> &PersistedId {
+        &self.codes
+    }
+
+    /// Returns the id of the persisted index of the codes
+    pub fn code_index(&self) -> &PersistedId {
+        &self.code_index
+    }
+}
+
 /// The main network state, includes the full state of contracts.
 #[derive(Clone, Default)]
 pub struct NetworkState {
     block_height: u64,
+    nonce: u64,
+    code_count: u64,
     contracts: Hamt<ContractId, Contract, ()>,
+    codes: Hamt<CodeHash, Code, ()>,
+    /// The hash of every code stored, by [`Code::index`]
+    code_index: Hamt<u64, CodeHash, ()>,
     modules: Rc<RefCell<HashMap<ContractId, BoxedHostModule>>>,
+    module_cache: RefCell<ModuleCache>,
+    schedules: ScheduleRegistry,
+    deploy_policy: DeployPolicy,
+    journal: Vec<Checkpoint>,
 }
 
 // Manual implementation of `Canon` to ignore the "modules" which needs to be
-// re-instantiated on program initialization.
+// re-instantiated on program initialization, the "module_cache" which is
+// rebuilt lazily, the "schedules" and "deploy_policy" which are part of the
+// node configuration, and the "journal" which only lives during transactions.
 impl Canon for NetworkState {
     fn encode(&self, sink: &mut Sink) {
         self.block_height.encode(sink);
+        self.nonce.encode(sink);
+        self.code_count.encode(sink);
         self.contracts.encode(sink);
+        self.codes.encode(sink);
+        self.code_index.encode(sink);
     }
 
     fn decode(source: &mut Source) -> Result<Self, CanonError> {
         Ok(NetworkState {
             block_height: u64::decode(source)?,
+            nonce: u64::decode(source)?,
+            code_count: u64::decode(source)?,
             contracts: Hamt::decode(source)?,
+            codes: Hamt::decode(source)?,
+            code_index: Hamt::decode(source)?,
             modules: Rc::new(RefCell::new(HashMap::new())),
+            module_cache: RefCell::new(ModuleCache::default()),
+            schedules: ScheduleRegistry::default(),
+            deploy_policy: DeployPolicy::default(),
+            journal: vec![],
         })
     }
 
     fn encoded_len(&self) -> usize {
         Canon::encoded_len(&self.block_height)
+            + Canon::encoded_len(&self.nonce)
+            + Canon::encoded_len(&self.code_count)
             + Canon::encoded_len(&self.contracts)
+            + Canon::encoded_len(&self.codes)
+            + Canon::encoded_len(&self.code_index)
     }
 }
 
 impl NetworkState {
+    /// The deployer of the contracts deployed directly on the
+    /// [`NetworkState`], rather than by another contract
+    pub const DEPLOYER: ContractId = ContractId::reserved(0);
+
     /// Returns a [`NetworkState`] for a specific block height
     pub fn with_block_height(block_height: u64) -> Self {
         Self {
             block_height,
+            nonce: 0,
+            code_count: 0,
             contracts: Hamt::default(),
+            codes: Hamt::default(),
+            code_index: Hamt::default(),
             modules: Rc::new(RefCell::new(HashMap::new())),
+            module_cache: RefCell::new(ModuleCache::default()),
+            schedules: ScheduleRegistry::default(),
+            deploy_policy: DeployPolicy::default(),
+            journal: vec![],
         }
     }
 
+    /// Sets the [`Schedule`] active from genesis, used to instrument the
+    /// contracts and to price the calls executed on this [`NetworkState`].
+    ///
+    /// Any schedule previously registered is discarded.
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedules = ScheduleRegistry::new(schedule);
+        self
+    }
+
+    /// Sets the number of parsed modules kept in memory, so that calls to
+    /// the most used contracts skip parsing and validating their code. A
+    /// capacity of zero disables the cache.
+    ///
+    /// Each clone of the [`NetworkState`] has its own cache.
+    pub fn with_module_cache_capacity(mut self, capacity: usize) -> Self {
+        self.module_cache = RefCell::new(ModuleCache::new(capacity));
+        self
+    }
+
+    /// Sets the [`DeployPolicy`] used to instrument the contracts deployed on
+    /// this [`NetworkState`]
+    pub fn with_deploy_policy(mut self, policy: DeployPolicy) -> Self {
+        self.deploy_policy = policy;
+        self
+    }
+
+    /// Returns the [`DeployPolicy`] contracts are deployed with
+    pub fn deploy_policy(&self) -> &DeployPolicy {
+        &self.deploy_policy
+    }
+
+    /// Registers a [`Schedule`] active from the block `height` onwards.
+    ///
+    /// Schedule versions must strictly increase with their activation height
+    /// and cannot be registered twice, otherwise
+    /// [`VMError::InvalidSchedule`] is returned.
+    pub fn register_schedule(
+        &mut self,
+        height: u64,
+        schedule: Schedule,
+    ) -> Result<(), VMError> {
+        self.schedules.register(height, schedule)
+    }
+
     #[cfg(feature = "persistence")]
-    /// Persists the contracts stored on the [`NetworkState`] specifying a
-    /// backend ctor function.
+    /// Persists the contracts and the code stored on the [`NetworkState`]
+    /// specifying a backend ctor function.
     pub fn persist(
         &self,
         ctor: fn() -> Result<DiskBackend, PersistError>,
-    ) -> Result<PersistedId, PersistError> {
-        Persistence::persist(&BackendCtor::new(ctor), &self.contracts)
+    ) -> Result<NetworkStateId, PersistError> {
+        let ctor = BackendCtor::new(ctor);
+
+        Ok(NetworkStateId {
+            nonce: self.nonce,
+            code_count: self.code_count,
+            contracts: Persistence::persist(&ctor, &self.contracts)?,
+            codes: Persistence::persist(&ctor, &self.codes)?,
+            code_index: Persistence::persist(&ctor, &self.code_index)?,
+        })
     }
 
     #[cfg(feature = "persistence")]
-    /// Given a [`PersistedId`] restores the [`Hamt`] which stores the contracts
-    /// of the entire blockchain state.
-    pub fn restore(mut self, id: PersistedId) -> Result<Self, PersistError> {
-        self.contracts = Hamt::from_generic(&id.restore()?)?;
+    /// Given a [`NetworkStateId`] restores the [`Hamt`]s which store the
+    /// contracts and the code of the entire blockchain state.
+    pub fn restore(mut self, id: NetworkStateId) -> Result<Self, PersistError> {
+        self.nonce = id.nonce;
+        self.code_count = id.code_count;
+        self.contracts = Hamt::from_generic(&id.contracts.restore()?)?;
+        self.codes = Hamt::from_generic(&id.codes.restore()?)?;
+        self.code_index = Hamt::from_generic(&id.code_index.restore()?)?;
+        self.module_cache.borrow_mut().clear();
+        self.journal.clear();
         Ok(self)
     }
 
     /// Deploys a contract to the state, returns the address of the created
-    /// contract or an error
+    /// contract or an error.
+    ///
+    /// The address is derived from [`NetworkState::DEPLOYER`], the number of
+    /// contracts previously deployed this way and the hash of the code, so
+    /// deploying the same code twice creates two distinct contracts.
     pub fn deploy(
         &mut self,
         contract: Contract,
     ) -> Result<ContractId, VMError> {
-        let id: ContractId = Store::hash(contract.bytecode()).into();
+        self.deploy_metered(contract, None)
+    }
+
+    /// Deploys a contract like [`NetworkState::deploy`], charging the given
+    /// meter, if any, for the code put into the state
+    fn deploy_metered(
+        &mut self,
+        contract: Contract,
+        gas_meter: Option<&mut GasMeter>,
+    ) -> Result<ContractId, VMError> {
+        let id = Contract::derive_id(
+            &Self::DEPLOYER,
+            self.nonce,
+            contract.code_hash(),
+        );
+
+        self.insert_deployed(id, contract, gas_meter)?;
+        self.nonce += 1;
+
+        Ok(id)
+    }
+
+    /// Deploys a contract to the state like [`NetworkState::deploy`], then
+    /// calls the `init` export of its code, if any, with the contract state
+    /// followed by the given constructor arguments. The state it returns
+    /// becomes the initial state of the contract.
+    ///
+    /// The code put into the state and the initialization are metered by the
+    /// given gas meter, and the contract is not deployed if either fails.
+    /// Otherwise the address of the contract is returned along with the
+    /// [`Event`]s emitted during the initialization.
+    pub fn deploy_with_init<A>(
+        &mut self,
+        contract: Contract,
+        arguments: A,
+        gas_meter: &mut GasMeter,
+    ) -> Result<(ContractId, Vec<Event>), VMError>
+    where
+        A: Canon,
+    {
+        let mut bytes = vec![0u8; arguments.encoded_len()];
+        arguments.encode(&mut Sink::new(&mut bytes[..]));
+
+        let (result, events, _) = self.execute(gas_meter, |context| {
+            let (state, gas_meter) = context.state_and_gas_meter();
+            let id = state.deploy_metered(contract, Some(gas_meter))?;
+
+            context.init(id, &bytes)?;
+            Ok(id)
+        });
+
+        result.map(|id| (id, events))
+    }
+
+    /// Deploys a contract to the state at the address derived from
+    /// [`NetworkState::DEPLOYER`], the given salt and the hash of the code,
+    /// returns the address of the created contract or an error
+    pub fn deploy_with_salt(
+        &mut self,
+        contract: Contract,
+        salt: [u8; 32],
+    ) -> Result<ContractId, VMError> {
+        let id = Contract::derive_id_with_salt(
+            &Self::DEPLOYER,
+            &salt,
+            contract.code_hash(),
+        );
 
         self.deploy_with_id(id, contract)
     }
 
-    /// Deploys a contract to the state with the given id / address
+    /// Deploys a contract to the state like [`NetworkState::deploy`], charging
+    /// the meter for every byte of code put into the state. Code already
+    /// stored is not charged again.
+    pub fn deploy_with_gas(
+        &mut self,
+        contract: Contract,
+        gas_meter: &mut GasMeter,
+    ) -> Result<ContractId, VMError> {
+        self.deploy_metered(contract, Some(gas_meter))
+    }
+
+    /// Deploys a contract to the state with the given id / address, fails with
+    /// [`VMError::ContractAlreadyExists`] if the address is taken
     pub fn deploy_with_id(
         &mut self,
         id: ContractId,
         contract: Contract,
     ) -> Result<ContractId, VMError> {
+        self.insert_deployed(id, contract, None)
+    }
+
+    fn insert_deployed(
+        &mut self,
+        id: ContractId,
+        mut contract: Contract,
+        gas_meter: Option<&mut GasMeter>,
+    ) -> Result<ContractId, VMError> {
+        self.check_vacant(&id)?;
+
+        let code = contract.take_code();
+        self.register_code(*contract.code_hash(), &code, gas_meter)?;
+
+        self.insert_contract(id, contract)
+    }
+
+    /// Deploys a contract running code already stored in the state, with the
+    /// given initial state, returns the address of the created contract or an
+    /// error.
+    ///
+    /// The address is derived like in [`NetworkState::deploy`].
+    pub fn deploy_from_code_hash<State>(
+        &mut self,
+        code_hash: CodeHash,
+        state: State,
+    ) -> Result<ContractId, VMError>
+    where
+        State: Canon,
+    {
+        self.get_code(&code_hash)?;
+
+        let state = ContractState::from_canon(&state);
+        self.deploy(Contract::with_code_hash(state, code_hash))
+    }
+
+    /// Deploys a contract on behalf of another contract, at the address
+    /// derived from the deployer, its nonce and the hash of the code
+    pub(crate) fn deploy_by(
+        &mut self,
+        deployer: &ContractId,
+        contract: Contract,
+        gas_meter: &mut GasMeter,
+    ) -> Result<ContractId, VMError> {
+        let nonce = self.get_contract(deployer)?.nonce();
+        let id = Contract::derive_id(deployer, nonce, contract.code_hash());
+
+        self.insert_deployed(id, contract, Some(gas_meter))?;
+        self.get_contract_mut(deployer)?.increment_nonce();
+
+        Ok(id)
+    }
+
+    /// Stores the given bytecode, instrumented under the active [`Schedule`],
+    /// unless code with the same hash is already stored.
+    ///
+    /// The given meter, if any, is charged for every byte of code stored, so
+    /// code already stored is not charged again.
+    fn register_code(
+        &mut self,
+        code_hash: CodeHash,
+        bytecode: &[u8],
+        gas_meter: Option<&mut GasMeter>,
+    ) -> Result<(), VMError> {
+        if self
+            .codes
+            .get(&code_hash)
+            .map_err(VMError::from_store_error)?
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        if let Some(gas_meter) = gas_meter {
+            let cost = self.schedule().put_code_per_byte_cost;
+
+            if gas_meter
+                .charge(cost.saturating_mul(bytecode.len() as Gas))
+                .is_out_of_gas()
+            {
+                return Err(VMError::OutOfGas);
+            }
+        }
+
+        let mut code = Code::new(bytecode.to_vec(), self.code_count);
+        code.instrument(self.schedule(), &self.deploy_policy)?;
+
+        resolver::validate_imports(code.instrumented_bytecode())?;
+
+        self.record_code(&code_hash)?;
+        self.code_index
+            .insert(self.code_count, code_hash)
+            .map_err(VMError::from_store_error)?;
+        self.codes
+            .insert(code_hash, code)
+            .map_err(VMError::from_store_error)?;
+        self.code_count += 1;
+
+        Ok(())
+    }
+
+    /// Replaces the code of a deployed contract, keeping its state.
+    ///
+    /// If the new code exports a `migrate` function, it is called with the
+    /// old state and returns the new one. The upgrade is reverted if the
+    /// migration fails, otherwise the [`Event`]s emitted during the
+    /// migration are returned.
+    pub fn upgrade(
+        &mut self,
+        contract_id: ContractId,
+        code: Vec<u8>,
+        gas_meter: &mut GasMeter,
+    ) -> Result<Vec<Event>, VMError> {
+        let code_hash = Store::hash(&code);
+
+        let (result, events, _) = self.execute(gas_meter, |context| {
+            let (state, gas_meter) = context.state_and_gas_meter();
+
+            state.register_code(code_hash, &code, Some(gas_meter))?;
+            state.set_code(&contract_id, code_hash)?;
+
+            context.migrate(contract_id)
+        });
+
+        result.map(|_| events)
+    }
+
+    /// Replaces the code of a deployed contract on behalf of `caller`, which
+    /// must be either the contract itself or its admin
+    pub(crate) fn upgrade_by(
+        &mut self,
+        caller: &ContractId,
+        contract_id: &ContractId,
+        code_hash: CodeHash,
+    ) -> Result<(), VMError> {
+        let contract = self.get_contract(contract_id)?;
+
+        if caller != contract_id && contract.admin() != Some(caller) {
+            return Err(VMError::UnauthorizedUpgrade);
+        }
+
+        drop(contract);
+        self.get_code(&code_hash)?;
+
+        self.set_code(contract_id, code_hash)
+    }
+
+    /// Switches the given contract to the code stored under `code_hash`,
+    /// releasing the code it was running
+    fn set_code(
+        &mut self,
+        contract_id: &ContractId,
+        code_hash: CodeHash,
+    ) -> Result<(), VMError> {
+        let old_hash = *self.get_contract(contract_id)?.code_hash();
+
+        if old_hash == code_hash {
+            return Ok(());
+        }
+
+        self.get_contract_mut(contract_id)?.set_code_hash(code_hash);
+        self.retain_code(&code_hash)?;
+        self.release_code(&old_hash)
+    }
+
+    /// Removes a contract from the state, crediting its balance to the
+    /// `beneficiary`. Its code is removed as well, unless other contracts are
+    /// running it.
+    ///
+    /// Fails with [`VMError::InvalidBeneficiary`] if the beneficiary is the
+    /// contract itself, since its balance would be lost.
+    pub fn remove_contract(
+        &mut self,
+        contract_id: &ContractId,
+        beneficiary: &ContractId,
+    ) -> Result<(), VMError> {
+        let (balance, code_hash) = {
+            let contract = self.get_contract(contract_id)?;
+            (contract.balance(), *contract.code_hash())
+        };
+
+        if contract_id == beneficiary {
+            return Err(VMError::InvalidBeneficiary);
+        }
+
+        self.get_contract_mut(beneficiary)?.credit(balance)?;
+
+        self.record(contract_id)?;
+        self.contracts
+            .remove(contract_id)
+            .map_err(VMError::from_store_error)?;
+
+        self.release_code(&code_hash)
+    }
+
+    /// Adds a reference to the code stored under the given hash
+    fn retain_code(&mut self, code_hash: &CodeHash) -> Result<(), VMError> {
+        self.record_code(code_hash)?;
+
+        self.codes
+            .get_mut(code_hash)
+            .map_err(VMError::from_store_error)?
+            .ok_or(VMError::UnknownCode)?
+            .retain();
+
+        Ok(())
+    }
+
+    /// Releases a reference to the code stored under the given hash, removing
+    /// the code once no contract runs it anymore
+    fn release_code(&mut self, code_hash: &CodeHash) -> Result<(), VMError> {
+        self.record_code(code_hash)?;
+
+        let (references, index) = {
+            let mut code = self
+                .codes
+                .get_mut(code_hash)
+                .map_err(VMError::from_store_error)?
+                .ok_or(VMError::UnknownCode)?;
+            (code.release(), code.index())
+        };
+
+        if references == 0 {
+            self.codes
+                .remove(code_hash)
+                .map_err(VMError::from_store_error)?;
+            self.code_index
+                .remove(&index)
+                .map_err(VMError::from_store_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_vacant(&self, id: &ContractId) -> Result<(), VMError> {
+        match self.contracts.get(id).map_err(VMError::from_store_error)? {
+            Some(_) => Err(VMError::ContractAlreadyExists(*id)),
+            None => Ok(()),
+        }
+    }
+
+    fn insert_contract(
+        &mut self,
+        id: ContractId,
+        contract: Contract,
+    ) -> Result<ContractId, VMError> {
+        self.record(&id)?;
+        self.retain_code(contract.code_hash())?;
+
         self.contracts
-            .insert(id, contract.instrument()?)
+            .insert(id, contract)
             .map_err(VMError::from_store_error)?;
+
         Ok(id)
     }
 
@@ -117,11 +627,25 @@ impl NetworkState {
             .unwrap_or(Err(VMError::UnknownContract))
     }
 
+    /// Returns a reference to the code stored under the given hash
+    pub fn get_code<'a>(
+        &'a self,
+        code_hash: &CodeHash,
+    ) -> Result<impl Deref<Target = Code> + 'a, VMError> {
+        self.codes
+            .get(code_hash)
+            .map_err(VMError::from_store_error)
+            .transpose()
+            .unwrap_or(Err(VMError::UnknownCode))
+    }
+
     /// Returns a reference to the specified contracts state
     pub fn get_contract_mut<'a>(
         &'a mut self,
         contract_id: &ContractId,
     ) -> Result<impl DerefMut<Target = Contract> + 'a, VMError> {
+        self.record(contract_id)?;
+
         self.contracts
             .get_mut(contract_id)
             .map_err(VMError::from_store_error)
@@ -129,6 +653,224 @@ impl NetworkState {
             .unwrap_or(Err(VMError::UnknownContract))
     }
 
+    /// Begins a checkpoint, recording the nonce, and the contracts and code
+    /// modified from now on so that the changes can be reverted.
+    ///
+    /// Checkpoints can be nested, each one must be closed with either
+    /// [`NetworkState::commit_checkpoint`] or
+    /// [`NetworkState::revert_checkpoint`].
+    pub fn begin_checkpoint(&mut self) {
+        self.journal.push(Checkpoint {
+            nonce: self.nonce,
+            code_count: self.code_count,
+            ..Checkpoint::default()
+        });
+    }
+
+    /// Commits the changes made since the last checkpoint began, making them
+    /// part of the enclosing checkpoint if any.
+    pub fn commit_checkpoint(&mut self) {
+        if let Some(checkpoint) = self.journal.pop() {
+            if let Some(parent) = self.journal.last_mut() {
+                for (id, original) in checkpoint.contracts {
+                    // The parent keeps the value it saw first
+                    parent.contracts.entry(id).or_insert(original);
+                }
+                for (hash, original) in checkpoint.codes {
+                    parent.codes.entry(hash).or_insert(original);
+                }
+            }
+        }
+    }
+
+    /// Reverts the changes made since the last checkpoint began
+    pub fn revert_checkpoint(&mut self) -> Result<(), VMError> {
+        if let Some(checkpoint) = self.journal.pop() {
+            self.nonce = checkpoint.nonce;
+            self.code_count = checkpoint.code_count;
+
+            for (id, original) in checkpoint.contracts {
+                match original {
+                    Some(contract) => {
+                        self.contracts
+                            .insert(id, contract)
+                            .map_err(VMError::from_store_error)?;
+                    }
+                    None => {
+                        self.contracts
+                            .remove(&id)
+                            .map_err(VMError::from_store_error)?;
+                    }
+                }
+            }
+
+            for (hash, original) in checkpoint.codes {
+                let current = self
+                    .codes
+                    .remove(&hash)
+                    .map_err(VMError::from_store_error)?;
+
+                if let Some(code) = current {
+                    self.code_index
+                        .remove(&code.index())
+                        .map_err(VMError::from_store_error)?;
+                }
+
+                if let Some(code) = original {
+                    self.code_index
+                        .insert(code.index(), hash)
+                        .map_err(VMError::from_store_error)?;
+                    self.codes
+                        .insert(hash, code)
+                        .map_err(VMError::from_store_error)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the current value of the given contract in the open
+    /// checkpoint, unless already recorded.
+    fn record(&mut self, contract_id: &ContractId) -> Result<(), VMError> {
+        let recorded = match self.journal.last() {
+            Some(checkpoint) => checkpoint.contracts.contains_key(contract_id),
+            None => return Ok(()),
+        };
+
+        if !recorded {
+            let original = self
+                .contracts
+                .get(contract_id)
+                .map_err(VMError::from_store_error)?
+                .map(|contract| (*contract).clone());
+
+            if let Some(checkpoint) = self.journal.last_mut() {
+                checkpoint.contracts.insert(*contract_id, original);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the current value of the code stored under the given hash in
+    /// the open checkpoint, unless already recorded.
+    fn record_code(&mut self, code_hash: &CodeHash) -> Result<(), VMError> {
+        let recorded = match self.journal.last() {
+            Some(checkpoint) => checkpoint.codes.contains_key(code_hash),
+            None => return Ok(()),
+        };
+
+        if !recorded {
+            let original = self
+                .codes
+                .get(code_hash)
+                .map_err(VMError::from_store_error)?
+                .map(|code| (*code).clone());
+
+            if let Some(checkpoint) = self.journal.last_mut() {
+                checkpoint.codes.insert(*code_hash, original);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Re-instruments the code of the given contract from its original
+    /// bytecode, if it was instrumented under a [`Schedule`] or a
+    /// [`DeployPolicy`] other than the active ones, and stores the result.
+    ///
+    /// Outdated code is re-instrumented in memory when called, leaving the
+    /// stored code untouched. Since the stored code is part of the state,
+    /// every node must re-instrument it at the same point, e.g. when a new
+    /// schedule activates.
+    pub fn reinstrument(
+        &mut self,
+        contract_id: &ContractId,
+    ) -> Result<(), VMError> {
+        let code_hash = *self.get_contract(contract_id)?.code_hash();
+        self.reinstrument_code(&code_hash)
+    }
+
+    /// Re-instruments every code stored in the state, like
+    /// [`NetworkState::reinstrument`]
+    pub fn reinstrument_all(&mut self) -> Result<(), VMError> {
+        for index in 0..self.code_count {
+            // indices of removed code are left empty
+            let code_hash = match self
+                .code_index
+                .get(&index)
+                .map_err(VMError::from_store_error)?
+            {
+                Some(code_hash) => *code_hash,
+                None => continue,
+            };
+
+            self.reinstrument_code(&code_hash)?;
+        }
+
+        Ok(())
+    }
+
+    fn reinstrument_code(
+        &mut self,
+        code_hash: &CodeHash,
+    ) -> Result<(), VMError> {
+        let schedule = self.schedule().clone();
+        let policy = self.deploy_policy;
+
+        if self
+            .get_code(code_hash)?
+            .is_instrumented_under(&schedule, &policy)
+        {
+            return Ok(());
+        }
+
+        self.record_code(code_hash)?;
+        self.codes
+            .get_mut(code_hash)
+            .map_err(VMError::from_store_error)?
+            .ok_or(VMError::UnknownCode)?
+            .instrument(&schedule, &policy)?;
+
+        Ok(())
+    }
+
+    /// Returns the parsed and validated module of the given contract,
+    /// instrumented under the active [`Schedule`].
+    ///
+    /// Modules are cached per [`CodeHash`], [`Schedule`] version and
+    /// [`DeployPolicy`], so only the first call to a contract running some
+    /// code pays for parsing and validating it, as long as the module is not
+    /// evicted from the cache. Code stored under an older schedule or another
+    /// policy is re-instrumented on a miss.
+    pub(crate) fn get_module(
+        &self,
+        contract_id: &ContractId,
+    ) -> Result<Rc<wasmi::Module>, VMError> {
+        let code_hash = *self.get_contract(contract_id)?.code_hash();
+        let schedule = self.schedule();
+        let policy = &self.deploy_policy;
+        let key = (code_hash, schedule.version, policy.id());
+
+        if let Some(module) = self.module_cache.borrow_mut().get(&key) {
+            return Ok(module);
+        }
+
+        let code = self.get_code(&code_hash)?;
+        let module = if code.is_instrumented_under(schedule, policy) {
+            wasmi::Module::from_buffer(code.instrumented_bytecode())?
+        } else {
+            let instrumented = code.instrumented_under(schedule, policy)?;
+            wasmi::Module::from_buffer(&instrumented)?
+        };
+        let module = Rc::new(module);
+
+        self.module_cache.borrow_mut().insert(key, module.clone());
+
+        Ok(module)
+    }
+
     /// Returns a reference to the map of registered host modules
     pub fn modules(
         &self,
@@ -141,6 +883,11 @@ impl NetworkState {
         self.block_height
     }
 
+    /// Returns the [`Schedule`] active at the state's block height
+    pub fn schedule(&self) -> &Schedule {
+        self.schedules.at(self.block_height)
+    }
+
     /// Query the contract at address `target`
     pub fn query<A, R>(
         &mut self,
@@ -170,22 +917,266 @@ impl NetworkState {
         A: Canon,
         R: Canon,
     {
-        // Fork the current network's state
-        let mut fork = self.clone();
+        let (result, _, _) = self.apply(target, None, &transaction, gas_meter);
+        result
+    }
 
-        // Use the forked state to execute the transaction
-        let mut context = CallContext::new(&mut fork, gas_meter);
+    /// Transact with the contract at address `target`, moving `value` from
+    /// the balance of the `sender` to the balance of the target along with
+    /// the call. The value is only moved if the transaction succeeds.
+    pub fn transact_with_value<A, R>(
+        &mut self,
+        sender: ContractId,
+        target: ContractId,
+        value: u64,
+        transaction: A,
+        gas_meter: &mut GasMeter,
+    ) -> Result<R, VMError>
+    where
+        A: Canon,
+        R: Canon,
+    {
+        let (result, _, _) =
+            self.apply(target, Some((sender, value)), &transaction, gas_meter);
+        result
+    }
 
-        let (_, result) =
-            context.transact(target, Transaction::from_canon(&transaction))?;
+    /// Creates `value` and credits it to the balance of the given contract,
+    /// increasing the total supply, e.g. to pay block rewards.
+    ///
+    /// Value only enters the state this way, every other operation moves it
+    /// between contracts.
+    pub fn mint(
+        &mut self,
+        contract_id: &ContractId,
+        value: u64,
+    ) -> Result<(), VMError> {
+        self.get_contract_mut(contract_id)?.credit(value)
+    }
+
+    /// Destroys `value` from the balance of the given contract, decreasing
+    /// the total supply
+    pub fn burn(
+        &mut self,
+        contract_id: &ContractId,
+        value: u64,
+    ) -> Result<(), VMError> {
+        self.get_contract_mut(contract_id)?.debit(value)
+    }
+
+    /// Moves `value` from the balance of `from` to the balance of `to`
+    pub(crate) fn transfer(
+        &mut self,
+        from: &ContractId,
+        to: &ContractId,
+        value: u64,
+    ) -> Result<(), VMError> {
+        self.get_contract_mut(from)?.debit(value)?;
+        self.get_contract_mut(to)?.credit(value)
+    }
+
+    /// Transact with the contract at address `target`, returning the
+    /// [`Event`]s emitted during the transaction alongside its result.
+    ///
+    /// No event is returned if the transaction fails.
+    pub fn transact_with_events<A, R>(
+        &mut self,
+        target: ContractId,
+        transaction: A,
+        gas_meter: &mut GasMeter,
+    ) -> Result<(R, Vec<Event>), VMError>
+    where
+        A: Canon,
+        R: Canon,
+    {
+        let (result, events, _) =
+            self.apply(target, None, &transaction, gas_meter);
+        result.map(|ret| (ret, events))
+    }
+
+    /// Transact with the contract at address `target`, returning a
+    /// [`Receipt`] describing the outcome of the transaction.
+    ///
+    /// The state is only updated if the transaction succeeds.
+    pub fn transact_with_receipt<A, R>(
+        &mut self,
+        target: ContractId,
+        transaction: A,
+        gas_meter: &mut GasMeter,
+    ) -> Receipt<R>
+    where
+        A: Canon,
+        R: Canon,
+    {
+        let spent = gas_meter.spent();
 
-        let ret = result.cast().map_err(VMError::from_store_error)?;
+        let (result, events, calls) =
+            self.apply(target, None, &transaction, gas_meter);
 
-        // If we reach this point, everything went well and we can use the
-        // updates made in the forked state.
-        *self = fork;
+        let gas_spent = gas_meter.spent() - spent;
+
+        match result {
+            Ok(ret) => Receipt {
+                status: TransactionStatus::Success,
+                ret: Some(ret),
+                gas_spent,
+                events,
+                calls,
+            },
+            Err(err) => Receipt {
+                status: err.into(),
+                ret: None,
+                gas_spent,
+                events: vec![],
+                calls,
+            },
+        }
+    }
+
+    /// Estimates the gas needed by a transaction with the contract at address
+    /// `target`.
+    ///
+    /// The transaction runs with an unbounded gas meter on a copy of the
+    /// state, which is left untouched whether the transaction succeeds or
+    /// not. If it succeeds, it is run again on copies of the state to search
+    /// for the lowest gas limit it succeeds with.
+    pub fn estimate_gas<A, R>(
+        &self,
+        target: ContractId,
+        transaction: A,
+    ) -> GasEstimate<R>
+    where
+        A: Canon,
+        R: Canon,
+    {
+        let mut state = self.clone();
+        let mut gas_meter = GasMeter::with_limit(Gas::MAX);
+
+        let (result, _, calls) =
+            state.apply(target, None, &transaction, &mut gas_meter);
+
+        let peak_nested_gas = calls
+            .iter()
+            .flat_map(Call::calls)
+            .map(Call::gas_spent)
+            .max()
+            .unwrap_or(0);
+
+        let gas_spent = gas_meter.spent();
+        let mut required_limit = gas_spent.saturating_add(1);
+
+        if result.is_ok() {
+            let succeeds_with = |gas_limit| {
+                let mut state = self.clone();
+                let mut gas_meter = GasMeter::with_limit(gas_limit);
+
+                let (result, _, _) = state.apply::<_, R>(
+                    target,
+                    None,
+                    &transaction,
+                    &mut gas_meter,
+                );
+                result.is_ok()
+            };
+
+            // the reserves kept back by the callers make the lowest limit
+            // higher than the gas spent, so find an upper bound first
+            let mut lower = gas_spent;
+            while required_limit < Gas::MAX && !succeeds_with(required_limit) {
+                lower = required_limit;
+                required_limit = required_limit.saturating_mul(2);
+            }
+
+            while required_limit - lower > 1 {
+                let middle = lower + (required_limit - lower) / 2;
+                if succeeds_with(middle) {
+                    required_limit = middle;
+                } else {
+                    lower = middle;
+                }
+            }
+        }
+
+        GasEstimate {
+            gas_spent,
+            required_limit,
+            peak_nested_gas,
+            result,
+        }
+    }
+
+    /// Executes a transaction within a checkpoint, reverting its changes if
+    /// it fails.
+    fn apply<A, R>(
+        &mut self,
+        target: ContractId,
+        transfer: Option<(ContractId, u64)>,
+        transaction: &A,
+        gas_meter: &mut GasMeter,
+    ) -> (Result<R, VMError>, Vec<Event>, Vec<Call>)
+    where
+        A: Canon,
+        R: Canon,
+    {
+        self.execute(gas_meter, |context| {
+            let transaction = Transaction::from_canon(transaction);
+
+            match transfer {
+                Some((sender, value)) => context.transact_with_value(
+                    sender,
+                    target,
+                    transaction,
+                    value,
+                ),
+                None => context.transact(target, transaction),
+            }
+            .and_then(|(_, result)| {
+                result.cast().map_err(VMError::from_store_error)
+            })
+        })
+    }
+
+    /// Performs `call` with a new [`CallContext`] within a checkpoint,
+    /// returning its result along with the events emitted and the tree of
+    /// calls performed.
+    ///
+    /// The changes are reverted if the call fails.
+    fn execute<T, C>(
+        &mut self,
+        gas_meter: &mut GasMeter,
+        call: C,
+    ) -> (Result<T, VMError>, Vec<Event>, Vec<Call>)
+    where
+        C: FnOnce(&mut CallContext) -> Result<T, VMError>,
+    {
+        self.begin_checkpoint();
+
+        let mut context = CallContext::new(self, gas_meter);
+        let mut result = call(&mut context);
+
+        let (events, calls, destructed) = context.into_parts();
+
+        // Contracts destructing themselves are removed only if the
+        // call succeeded, in the order they destructed, so a beneficiary
+        // destructing itself later passes the balance on.
+        if result.is_ok() {
+            result = destructed
+                .iter()
+                .try_for_each(|(id, beneficiary)| {
+                    self.remove_contract(id, beneficiary)
+                })
+                .and(result);
+        }
+
+        // If everything went well we keep the changes, otherwise the state is
+        // restored as it was before the call.
+        if result.is_ok() {
+            self.commit_checkpoint();
+        } else if let Err(err) = self.revert_checkpoint() {
+            result = Err(err);
+        }
 
-        Ok(ret)
+        (result, events, calls)
     }
 
     /// Register a host-fn handler
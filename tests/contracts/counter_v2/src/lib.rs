@@ -0,0 +1,152 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// query ids
+pub const READ_VALUE: u8 = 0;
+
+// transaction ids
+pub const INCREMENT: u8 = 0;
+pub const UPGRADE: u8 = 1;
+
+/// A counter upgrading the `counter` contract, its state has the same layout
+/// but migrating it scales the value by ten
+#[derive(Clone, Canon, Debug)]
+pub struct CounterV2 {
+    junk: u32,
+    value: i32,
+}
+
+impl CounterV2 {
+    pub fn new(value: i32) -> Self {
+        CounterV2 {
+            junk: 0xffffffff,
+            value,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    use super::*;
+
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractId, ContractState, ReturnValue};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    mod ext {
+        extern "C" {
+            pub fn upgrade(contract_id: *const u8, code_hash: *const u8);
+        }
+    }
+
+    impl CounterV2 {
+        pub fn read_value(&self) -> i32 {
+            self.value
+        }
+
+        pub fn increment(&mut self) {
+            self.value += 1;
+        }
+
+        pub fn upgrade(&mut self, target: ContractId, code_hash: [u8; 32]) {
+            unsafe {
+                ext::upgrade(target.as_bytes().as_ptr(), code_hash.as_ptr())
+            }
+        }
+
+        pub fn migrate(&mut self) {
+            self.value *= 10;
+        }
+    }
+
+    fn query(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(&bytes[..]);
+
+        // read self.
+        let slf = CounterV2::decode(&mut source)?;
+
+        // read query id
+        let qid = u8::decode(&mut source)?;
+        match qid {
+            // read_value (&Self) -> i32
+            READ_VALUE => {
+                let ret = slf.read_value();
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&ret).encode(&mut sink);
+                Ok(())
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[no_mangle]
+    fn q(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        let _ = query(bytes);
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let mut slf = CounterV2::decode(&mut source)?;
+        // read transaction id
+        let tid = u8::decode(&mut source)?;
+        match tid {
+            // increment (&Self)
+            INCREMENT => slf.increment(),
+            // upgrade (&Self, target: ContractId, code_hash: [u8; 32])
+            UPGRADE => {
+                let (target, code_hash): (ContractId, [u8; 32]) =
+                    Canon::decode(&mut source)?;
+                slf.upgrade(target, code_hash);
+            }
+            _ => panic!(""),
+        }
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+
+        // return value ()
+        ReturnValue::from_canon(&()).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+
+    fn migration(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read the old state
+        let mut slf = CounterV2::decode(&mut source)?;
+        slf.migrate();
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn migrate(bytes: &mut [u8; PAGE_SIZE]) {
+        migration(bytes).unwrap()
+    }
+}
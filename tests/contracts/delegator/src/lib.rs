@@ -11,6 +11,7 @@ use canonical_derive::Canon;
 
 // query ids
 pub const DELEGATE_QUERY: u8 = 0;
+pub const DELEGATE_TRANSACTION_IN_QUERY: u8 = 1;
 
 // transaction ids
 pub const DELEGATE_TRANSACTION: u8 = 0;
@@ -67,6 +68,18 @@ mod hosted {
                 result.encode(&mut sink);
                 Ok(())
             }
+            DELEGATE_TRANSACTION_IN_QUERY => {
+                let (target, transaction): (ContractId, Transaction) =
+                    Canon::decode(&mut source)?;
+
+                let mut slf = slf;
+                let result = slf.delegate_transaction(&target, &transaction);
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                result.encode(&mut sink);
+                Ok(())
+            }
             _ => panic!(""),
         }
     }
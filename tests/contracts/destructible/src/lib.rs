@@ -0,0 +1,157 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// transaction ids
+pub const SELF_DESTRUCT: u8 = 0;
+pub const SELF_DESTRUCT_AND_PANIC: u8 = 1;
+/// Destructs the contract in favor of the beneficiary, then has the
+/// beneficiary destruct itself in favor of the next beneficiary
+pub const SELF_DESTRUCT_AND_FORWARD: u8 = 2;
+
+/// Topic of the event emitted by the contract when destructing itself from a
+/// hook, with the beneficiary as data
+pub const TOPIC: [u8; 32] = [0xde; 32];
+
+#[derive(Clone, Canon, Debug)]
+pub struct Destructible;
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    use super::*;
+
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractId, ContractState, ReturnValue, Transaction};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    mod ext {
+        extern "C" {
+            pub fn self_destruct(beneficiary: *const u8);
+            pub fn emit(
+                topics: *const u8,
+                topics_len: i32,
+                data: *const u8,
+                data_len: i32,
+            );
+        }
+    }
+
+    impl Destructible {
+        pub fn self_destruct(&self, beneficiary: ContractId) {
+            unsafe { ext::self_destruct(beneficiary.as_bytes().as_ptr()) }
+        }
+
+        pub fn self_destruct_and_forward(
+            &mut self,
+            beneficiary: ContractId,
+            next: ContractId,
+        ) {
+            self.self_destruct(beneficiary);
+
+            let transaction = Transaction::from_canon(&(SELF_DESTRUCT, next));
+            dusk_abi::transact_raw::<_>(self, &beneficiary, &transaction)
+                .unwrap();
+        }
+
+        // announces the destruction before destructing
+        pub fn announced_self_destruct(&self, beneficiary: ContractId) {
+            let data = beneficiary.as_bytes();
+            unsafe {
+                ext::emit(TOPIC.as_ptr(), 1, data.as_ptr(), data.len() as i32)
+            }
+            self.self_destruct(beneficiary)
+        }
+    }
+
+    #[no_mangle]
+    fn q(_bytes: &mut [u8; PAGE_SIZE]) {
+        panic!("no queries")
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let mut slf = Destructible::decode(&mut source)?;
+        // read transaction id and beneficiary
+        let (tid, beneficiary): (u8, ContractId) = Canon::decode(&mut source)?;
+        match tid {
+            SELF_DESTRUCT => slf.self_destruct(beneficiary),
+            SELF_DESTRUCT_AND_PANIC => {
+                slf.self_destruct(beneficiary);
+                panic!("destructed, then panicked")
+            }
+            SELF_DESTRUCT_AND_FORWARD => {
+                let next = ContractId::decode(&mut source)?;
+                slf.self_destruct_and_forward(beneficiary, next)
+            }
+            _ => panic!(""),
+        }
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+
+        // return value ()
+        ReturnValue::from_canon(&()).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+
+    fn migration(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // the old state is the beneficiary
+        let beneficiary = ContractId::decode(&mut source)?;
+
+        let slf = Destructible;
+        slf.announced_self_destruct(beneficiary);
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn migrate(bytes: &mut [u8; PAGE_SIZE]) {
+        migration(bytes).unwrap()
+    }
+
+    fn initialization(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let slf = Destructible::decode(&mut source)?;
+        // read constructor arguments
+        let beneficiary = ContractId::decode(&mut source)?;
+
+        slf.announced_self_destruct(beneficiary);
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return initial state
+        ContractState::from_canon(&slf).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn init(bytes: &mut [u8; PAGE_SIZE]) {
+        initialization(bytes).unwrap()
+    }
+}
@@ -0,0 +1,132 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// query ids
+pub const EMITTED: u8 = 0;
+
+// transaction ids
+pub const EMIT: u8 = 0;
+pub const EMIT_AND_PANIC: u8 = 1;
+
+/// Topic of the events emitted by the contract
+pub const TOPIC: [u8; 32] = [0xab; 32];
+
+#[derive(Clone, Canon, Debug, Default)]
+pub struct Events {
+    emitted: u32,
+}
+
+impl Events {
+    pub fn new() -> Self {
+        Events { emitted: 0 }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    extern crate alloc;
+
+    use super::*;
+
+    use alloc::vec::Vec;
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractState, ReturnValue};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    mod ext {
+        extern "C" {
+            pub fn emit(
+                topics: *const u8,
+                topics_len: i32,
+                data: *const u8,
+                data_len: i32,
+            );
+        }
+    }
+
+    impl Events {
+        pub fn emitted(&self) -> u32 {
+            self.emitted
+        }
+
+        pub fn emit(&mut self, data: Vec<u8>) {
+            self.emitted += 1;
+            unsafe {
+                ext::emit(TOPIC.as_ptr(), 1, data.as_ptr(), data.len() as i32)
+            }
+        }
+    }
+
+    fn query(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(&bytes[..]);
+
+        // read self.
+        let slf = Events::decode(&mut source)?;
+
+        // read query id
+        let qid = u8::decode(&mut source)?;
+        match qid {
+            EMITTED => {
+                let ret = slf.emitted();
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&ret).encode(&mut sink);
+                Ok(())
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[no_mangle]
+    fn q(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        let _ = query(bytes);
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let mut slf = Events::decode(&mut source)?;
+        // read transaction id
+        let tid = u8::decode(&mut source)?;
+        match tid {
+            EMIT => {
+                let data = Vec::<u8>::decode(&mut source)?;
+                slf.emit(data);
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                // return new state
+                ContractState::from_canon(&slf).encode(&mut sink);
+
+                // return value
+                ReturnValue::from_canon(&()).encode(&mut sink);
+                Ok(())
+            }
+            EMIT_AND_PANIC => {
+                let data = Vec::<u8>::decode(&mut source)?;
+                slf.emit(data);
+
+                panic!("Events emitted before a panic are discarded")
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+}
@@ -0,0 +1,200 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// query ids
+pub const DEPLOYED: u8 = 0;
+pub const DEPLOY_IN_QUERY: u8 = 1;
+
+// transaction ids
+pub const DEPLOY: u8 = 0;
+pub const DEPLOY_FROM_CODE_HASH: u8 = 1;
+pub const DEPLOY_AND_PANIC: u8 = 2;
+pub const DEPLOY_CODE_AND_PANIC: u8 = 3;
+
+#[derive(Clone, Canon, Debug, Default)]
+pub struct Factory {
+    deployed: u32,
+}
+
+impl Factory {
+    pub fn new() -> Self {
+        Factory { deployed: 0 }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    extern crate alloc;
+
+    use super::*;
+
+    use alloc::vec::Vec;
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractId, ContractState, ReturnValue};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    mod ext {
+        extern "C" {
+            pub fn deploy(
+                code: *const u8,
+                code_len: i32,
+                state: *const u8,
+                ret: &mut u8,
+            );
+            pub fn deploy_from_code_hash(
+                code_hash: *const u8,
+                state: *const u8,
+                ret: &mut u8,
+            );
+        }
+    }
+
+    impl Factory {
+        pub fn deployed(&self) -> u32 {
+            self.deployed
+        }
+
+        pub fn deploy(
+            &mut self,
+            code: Vec<u8>,
+            state: ContractState,
+        ) -> ContractId {
+            let mut buf = [0u8; PAGE_SIZE];
+            state.encode(&mut Sink::new(&mut buf[..]));
+
+            let mut id = [0u8; 32];
+            unsafe {
+                ext::deploy(
+                    code.as_ptr(),
+                    code.len() as i32,
+                    buf.as_ptr(),
+                    &mut id[0],
+                )
+            }
+
+            self.deployed += 1;
+            ContractId::from(id)
+        }
+
+        pub fn deploy_from_code_hash(
+            &mut self,
+            code_hash: [u8; 32],
+            state: ContractState,
+        ) -> ContractId {
+            let mut buf = [0u8; PAGE_SIZE];
+            state.encode(&mut Sink::new(&mut buf[..]));
+
+            let mut id = [0u8; 32];
+            unsafe {
+                ext::deploy_from_code_hash(
+                    code_hash.as_ptr(),
+                    buf.as_ptr(),
+                    &mut id[0],
+                )
+            }
+
+            self.deployed += 1;
+            ContractId::from(id)
+        }
+    }
+
+    fn query(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(&bytes[..]);
+
+        // read self.
+        let mut slf = Factory::decode(&mut source)?;
+
+        // read query id
+        let qid = u8::decode(&mut source)?;
+        match qid {
+            DEPLOYED => {
+                let ret = slf.deployed();
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&ret).encode(&mut sink);
+                Ok(())
+            }
+            DEPLOY_IN_QUERY => {
+                let (code_hash, state): ([u8; 32], ContractState) =
+                    Canon::decode(&mut source)?;
+
+                let ret = slf.deploy_from_code_hash(code_hash, state);
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&ret).encode(&mut sink);
+                Ok(())
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[no_mangle]
+    fn q(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        let _ = query(bytes);
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let mut slf = Factory::decode(&mut source)?;
+        // read transaction id
+        let tid = u8::decode(&mut source)?;
+        let ret = match tid {
+            DEPLOY => {
+                let (code, state): (Vec<u8>, ContractState) =
+                    Canon::decode(&mut source)?;
+
+                slf.deploy(code, state)
+            }
+            DEPLOY_FROM_CODE_HASH => {
+                let (code_hash, state): ([u8; 32], ContractState) =
+                    Canon::decode(&mut source)?;
+
+                slf.deploy_from_code_hash(code_hash, state)
+            }
+            DEPLOY_AND_PANIC => {
+                let (code_hash, state): ([u8; 32], ContractState) =
+                    Canon::decode(&mut source)?;
+
+                slf.deploy_from_code_hash(code_hash, state);
+                panic!("deployed, then panicked")
+            }
+            DEPLOY_CODE_AND_PANIC => {
+                let (code, state): (Vec<u8>, ContractState) =
+                    Canon::decode(&mut source)?;
+
+                slf.deploy(code, state);
+                panic!("deployed, then panicked")
+            }
+            _ => panic!(""),
+        };
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+
+        // return value
+        ReturnValue::from_canon(&ret).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+}
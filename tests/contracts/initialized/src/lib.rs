@@ -0,0 +1,103 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// query ids
+pub const READ_VALUE: u8 = 0;
+pub const IS_INITIALIZED: u8 = 1;
+
+/// A contract deriving its initial state from the arguments of its `init`
+/// export, which only accepts non-negative values
+#[derive(Clone, Canon, Debug, Default)]
+pub struct Initialized {
+    value: i32,
+    initialized: bool,
+}
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    use super::*;
+
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractState, ReturnValue};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    impl Initialized {
+        pub fn init(&mut self, value: i32) {
+            if value < 0 {
+                panic!("negative initial value")
+            }
+
+            self.value = value;
+            self.initialized = true;
+        }
+    }
+
+    fn query(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(&bytes[..]);
+
+        // read self.
+        let slf = Initialized::decode(&mut source)?;
+
+        // read query id
+        let qid = u8::decode(&mut source)?;
+        match qid {
+            // read_value (&Self) -> i32
+            READ_VALUE => {
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&slf.value).encode(&mut sink);
+                Ok(())
+            }
+            // is_initialized (&Self) -> bool
+            IS_INITIALIZED => {
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&slf.initialized).encode(&mut sink);
+                Ok(())
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[no_mangle]
+    fn q(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        let _ = query(bytes);
+    }
+
+    #[no_mangle]
+    fn t(_bytes: &mut [u8; PAGE_SIZE]) {
+        panic!("no transactions")
+    }
+
+    fn initialization(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let mut slf = Initialized::decode(&mut source)?;
+        // read constructor arguments
+        let value = i32::decode(&mut source)?;
+
+        slf.init(value);
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return initial state
+        ContractState::from_canon(&slf).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn init(bytes: &mut [u8; PAGE_SIZE]) {
+        initialization(bytes).unwrap()
+    }
+}
@@ -0,0 +1,144 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// query ids, each passing the given offset to a host function
+pub const DEBUG: u8 = 0;
+pub const CALLEE: u8 = 1;
+pub const SIG: u8 = 2;
+pub const GET: u8 = 3;
+pub const PUT: u8 = 4;
+pub const HASH: u8 = 5;
+pub const QUERY: u8 = 6;
+
+// transaction ids, each passing the given offset to a host function
+pub const EMIT: u8 = 0;
+pub const TRANSACT: u8 = 1;
+
+// Offset past the end of any contract memory
+pub const PAST_THE_END: u32 = 0x7fff_0000;
+// Offset overflowing once the length of the accessed buffer is added
+pub const OVERFLOWING: u32 = 0xffff_fff0;
+
+#[derive(Clone, Canon, Debug, Default)]
+pub struct OutOfBounds;
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    use super::*;
+
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractState, ReturnValue};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    // Length of the buffers accessed at the given offset
+    const LEN: i32 = 0x100;
+
+    mod ext {
+        extern "C" {
+            pub fn sig(msg: *const u8, len: i32);
+            pub fn debug(buffer: *const u8, len: i32);
+            pub fn callee(buffer: *mut u8);
+            pub fn get(hash: *const u8, buffer: *mut u8, len: i32);
+            pub fn put(buffer: *const u8, len: i32, ret: *mut u8);
+            pub fn hash(buffer: *const u8, len: i32, ret: *mut u8);
+            pub fn query(target: *const u8, buffer: *mut u8);
+            pub fn transact(target: *const u8, buffer: *mut u8);
+            pub fn emit(
+                topics: *const u8,
+                topics_len: i32,
+                data: *const u8,
+                data_len: i32,
+            );
+        }
+    }
+
+    impl OutOfBounds {
+        pub fn access(&self, op: u8, ofs: u32) {
+            let ptr = ofs as *mut u8;
+            let mut buf = [0u8; PAGE_SIZE];
+
+            unsafe {
+                match op {
+                    DEBUG => ext::debug(ptr, LEN),
+                    CALLEE => ext::callee(ptr),
+                    SIG => ext::sig(ptr, LEN),
+                    GET => ext::get(buf.as_ptr(), ptr, LEN),
+                    PUT => ext::put(ptr, LEN, buf.as_mut_ptr()),
+                    HASH => ext::hash(ptr, LEN, buf.as_mut_ptr()),
+                    QUERY => ext::query(ptr, buf.as_mut_ptr()),
+                    _ => panic!(""),
+                }
+            }
+        }
+
+        pub fn emit(&self, ofs: u32) {
+            unsafe { ext::emit(ofs as *const u8, 1, ofs as *const u8, LEN) }
+        }
+
+        pub fn transact(&self, ofs: u32) {
+            let mut buf = [0u8; PAGE_SIZE];
+            unsafe { ext::transact(ofs as *const u8, buf.as_mut_ptr()) }
+        }
+    }
+
+    fn query(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(&bytes[..]);
+
+        // read self.
+        let slf = OutOfBounds::decode(&mut source)?;
+
+        // read query id and offset
+        let (qid, ofs): (u8, u32) = Canon::decode(&mut source)?;
+        slf.access(qid, ofs);
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        ReturnValue::from_canon(&()).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn q(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        let _ = query(bytes);
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let slf = OutOfBounds::decode(&mut source)?;
+
+        // read transaction id and offset
+        let (tid, ofs): (u8, u32) = Canon::decode(&mut source)?;
+        match tid {
+            EMIT => slf.emit(ofs),
+            TRANSACT => slf.transact(ofs),
+            _ => panic!(""),
+        }
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+
+        // return value
+        ReturnValue::from_canon(&()).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+}
@@ -0,0 +1,89 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// transaction ids
+pub const PUT: u8 = 0;
+pub const HASH: u8 = 1;
+
+#[derive(Clone, Canon, Debug, Default)]
+pub struct Storage;
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    extern crate alloc;
+
+    use super::*;
+
+    use alloc::vec::Vec;
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractState, ReturnValue};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    mod ext {
+        extern "C" {
+            pub fn put(ofs: *const u8, len: i32, ret: &mut u8);
+            pub fn hash(ofs: *const u8, len: i32, ret: &mut u8);
+        }
+    }
+
+    impl Storage {
+        pub fn put(&self, data: Vec<u8>) {
+            let mut hash = [0u8; 32];
+            unsafe { ext::put(data.as_ptr(), data.len() as i32, &mut hash[0]) }
+        }
+
+        pub fn hash(&self, data: Vec<u8>) {
+            let mut hash = [0u8; 32];
+            unsafe { ext::hash(data.as_ptr(), data.len() as i32, &mut hash[0]) }
+        }
+    }
+
+    #[no_mangle]
+    fn q(_bytes: &mut [u8; PAGE_SIZE]) {
+        panic!("no queries")
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let slf = Storage::decode(&mut source)?;
+        // read transaction id
+        let tid = u8::decode(&mut source)?;
+        match tid {
+            PUT => {
+                let data = Vec::<u8>::decode(&mut source)?;
+                slf.put(data);
+            }
+            HASH => {
+                let data = Vec::<u8>::decode(&mut source)?;
+                slf.hash(data);
+            }
+            _ => panic!(""),
+        }
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+
+        // return value
+        ReturnValue::from_canon(&()).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+}
@@ -0,0 +1,178 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// query ids
+pub const STATUS: u8 = 0;
+pub const TRY_QUERY: u8 = 1;
+
+// transaction ids
+pub const TRY_TRANSACT: u8 = 0;
+
+#[derive(Clone, Canon, Debug, Default)]
+pub struct TryCaller {
+    status: i32,
+}
+
+impl TryCaller {
+    pub fn new() -> Self {
+        TryCaller { status: 0 }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    use super::*;
+
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{
+        ContractId, ContractState, Query, ReturnValue, Transaction,
+    };
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    mod ext {
+        extern "C" {
+            pub fn try_query(
+                target: *const u8,
+                buf: &mut u8,
+                gas_limit: u64,
+            ) -> i32;
+            pub fn try_transact(
+                target: *const u8,
+                buf: &mut u8,
+                gas_limit: u64,
+            ) -> i32;
+        }
+    }
+
+    impl TryCaller {
+        pub fn status(&self) -> i32 {
+            self.status
+        }
+
+        // queries the target, returning the status of the call
+        pub fn try_query(
+            &self,
+            target: ContractId,
+            gas_limit: u64,
+            query: Query,
+        ) -> i32 {
+            let mut buf = [0u8; PAGE_SIZE];
+            query.encode(&mut Sink::new(&mut buf[..]));
+
+            unsafe {
+                ext::try_query(
+                    target.as_bytes().as_ptr(),
+                    &mut buf[0],
+                    gas_limit,
+                )
+            }
+        }
+
+        // transacts with the target, recording the status of the call
+        pub fn try_transact(
+            &mut self,
+            target: ContractId,
+            gas_limit: u64,
+            transaction: Transaction,
+        ) -> i32 {
+            let mut buf = [0u8; PAGE_SIZE];
+            {
+                let mut sink = Sink::new(&mut buf[..]);
+                ContractState::from_canon(self).encode(&mut sink);
+                transaction.encode(&mut sink);
+            }
+
+            self.status = unsafe {
+                ext::try_transact(
+                    target.as_bytes().as_ptr(),
+                    &mut buf[0],
+                    gas_limit,
+                )
+            };
+            self.status
+        }
+    }
+
+    fn query(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(&bytes[..]);
+
+        // read self.
+        let slf = TryCaller::decode(&mut source)?;
+
+        // read query id
+        let qid = u8::decode(&mut source)?;
+        match qid {
+            STATUS => {
+                let ret = slf.status();
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&ret).encode(&mut sink);
+                Ok(())
+            }
+            TRY_QUERY => {
+                let (target, gas_limit, query): (ContractId, u64, Query) =
+                    Canon::decode(&mut source)?;
+
+                let ret = slf.try_query(target, gas_limit, query);
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                ReturnValue::from_canon(&ret).encode(&mut sink);
+                Ok(())
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[no_mangle]
+    fn q(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        let _ = query(bytes);
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let mut slf = TryCaller::decode(&mut source)?;
+        // read transaction id
+        let tid = u8::decode(&mut source)?;
+        match tid {
+            TRY_TRANSACT => {
+                let (target, gas_limit, transaction): (
+                    ContractId,
+                    u64,
+                    Transaction,
+                ) = Canon::decode(&mut source)?;
+
+                let ret = slf.try_transact(target, gas_limit, transaction);
+
+                let mut sink = Sink::new(&mut bytes[..]);
+
+                // return new state
+                ContractState::from_canon(&slf).encode(&mut sink);
+
+                // return value
+                ReturnValue::from_canon(&ret).encode(&mut sink);
+                Ok(())
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+}
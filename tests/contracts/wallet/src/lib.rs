@@ -0,0 +1,164 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+#![cfg_attr(target_arch = "wasm32", no_std)]
+#![feature(core_intrinsics, lang_items, alloc_error_handler)]
+
+use canonical_derive::Canon;
+
+// query ids
+pub const BALANCE: u8 = 0;
+pub const RECEIVED: u8 = 1;
+
+// transaction ids
+pub const RECEIVE: u8 = 0;
+pub const REFUSE: u8 = 1;
+pub const FORWARD: u8 = 2;
+
+/// A contract receiving, refusing and forwarding the value sent along with
+/// its transactions
+#[derive(Clone, Canon, Debug, Default)]
+pub struct Wallet {
+    received: u64,
+}
+
+impl Wallet {
+    pub fn new() -> Self {
+        Wallet { received: 0 }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod hosted {
+    use super::*;
+
+    use canonical::{Canon, CanonError, Sink, Source};
+    use dusk_abi::{ContractId, ContractState, ReturnValue, Transaction};
+
+    const PAGE_SIZE: usize = 1024 * 4;
+
+    mod ext {
+        extern "C" {
+            pub fn balance() -> u64;
+            pub fn value() -> u64;
+            pub fn transact_with_value(
+                target: *const u8,
+                buf: &mut u8,
+                value: u64,
+            );
+        }
+    }
+
+    impl Wallet {
+        pub fn balance(&self) -> u64 {
+            unsafe { ext::balance() }
+        }
+
+        // accepts any value sent along with the call
+        pub fn receive(&mut self) -> u64 {
+            let value = unsafe { ext::value() };
+            self.received += value;
+            value
+        }
+
+        // only accepts calls without value
+        pub fn refuse(&self) {
+            if unsafe { ext::value() } > 0 {
+                panic!("not payable")
+            }
+        }
+
+        pub fn forward(
+            &self,
+            target: ContractId,
+            value: u64,
+            transaction: Transaction,
+        ) {
+            let mut buf = [0u8; PAGE_SIZE];
+            {
+                let mut sink = Sink::new(&mut buf[..]);
+                ContractState::from_canon(self).encode(&mut sink);
+                transaction.encode(&mut sink);
+            }
+
+            unsafe {
+                ext::transact_with_value(
+                    target.as_bytes().as_ptr(),
+                    &mut buf[0],
+                    value,
+                )
+            }
+        }
+    }
+
+    fn query(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(&bytes[..]);
+
+        // read self.
+        let slf = Wallet::decode(&mut source)?;
+
+        // read query id
+        let qid = u8::decode(&mut source)?;
+        let ret = match qid {
+            BALANCE => slf.balance(),
+            RECEIVED => slf.received,
+            _ => panic!(""),
+        };
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        ReturnValue::from_canon(&ret).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn q(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        let _ = query(bytes);
+    }
+
+    fn transaction(bytes: &mut [u8; PAGE_SIZE]) -> Result<(), CanonError> {
+        let mut source = Source::new(bytes);
+
+        // read self.
+        let mut slf = Wallet::decode(&mut source)?;
+        // read transaction id
+        let tid = u8::decode(&mut source)?;
+        let ret = match tid {
+            RECEIVE => slf.receive(),
+            REFUSE => {
+                slf.refuse();
+                0
+            }
+            FORWARD => {
+                let (target, value, transaction): (
+                    ContractId,
+                    u64,
+                    Transaction,
+                ) = Canon::decode(&mut source)?;
+
+                slf.forward(target, value, transaction);
+                value
+            }
+            _ => panic!(""),
+        };
+
+        let mut sink = Sink::new(&mut bytes[..]);
+
+        // return new state
+        ContractState::from_canon(&slf).encode(&mut sink);
+
+        // return value
+        ReturnValue::from_canon(&ret).encode(&mut sink);
+        Ok(())
+    }
+
+    #[no_mangle]
+    fn t(bytes: &mut [u8; PAGE_SIZE]) {
+        // todo, handle errors here
+        transaction(bytes).unwrap()
+    }
+}
@@ -10,13 +10,26 @@ use callee_2::Callee2;
 use caller::Caller;
 use counter::Counter;
 use counter_float::CounterFloat;
+use counter_v2::CounterV2;
 use delegator::Delegator;
-use dusk_abi::Transaction;
+use destructible::Destructible;
+use dusk_abi::{ContractState, Query, Transaction};
+use events::Events;
+use factory::Factory;
 use fibonacci::Fibonacci;
 use gas_consumed::GasConsumed;
-use rusk_vm::{Contract, ContractId, GasMeter, NetworkState, VMError};
+use initialized::Initialized;
+use out_of_bounds::OutOfBounds;
+use rusk_vm::{
+    CallKind, Contract, ContractId, DeployPolicy, GasMeter,
+    InstrumentalizationError, NetworkState, Schedule, TransactionStatus,
+    VMError, CALL_FAILED, CALL_OUT_OF_GAS, CALL_PANICKED, CALL_SUCCEEDED,
+};
 use self_snapshot::SelfSnapshot;
+use storage::Storage;
+use try_caller::TryCaller;
 use tx_vec::TxVec;
+use wallet::Wallet;
 
 fn fibonacci_reference(n: u64) -> u64 {
     if n < 2 {
@@ -421,9 +434,10 @@ fn gas_consumed_host_function_works() {
 
     let contract_id = network.deploy(contract).expect("Deploy error");
 
-    // 2050 is the gas held that is known will be spent in the contract
-    // after the `dusk_abi::gas_left()` call
-    let mut gas = GasMeter::with_range(2_050..1_000_000_000);
+    // 2066 is the gas held that is known will be spent after the
+    // `dusk_abi::gas_left()` call: 2050 in the contract, plus 16 for the
+    // bytes returned with the default `Schedule`
+    let mut gas = GasMeter::with_range(2_066..1_000_000_000);
 
     network
         .transact::<_, ()>(contract_id, gas_consumed::INCREMENT, &mut gas)
@@ -449,7 +463,7 @@ fn gas_consumed_host_function_works() {
 
     assert_eq!(
         gas.spent() - gas_consumed,
-        2_050,
+        2_066,
         "The gas spent minus the gas consumed should be equal to the gas held"
     );
 }
@@ -507,69 +521,1902 @@ fn out_of_gas_aborts_execution() {
     assert_eq!(gas.left(), 0);
 }
 
+#[test]
+fn schedule_drives_call_pricing() {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let spent_with = |schedule: Schedule| {
+        let contract = Contract::new(Counter::new(99), code.to_vec());
+
+        let mut network = NetworkState::default().with_schedule(schedule);
+        let contract_id = network.deploy(contract).expect("Deploy error");
+
+        let mut gas = GasMeter::with_limit(1_000_000_000);
+
+        network
+            .query::<_, i32>(contract_id, counter::READ_VALUE, &mut gas)
+            .expect("Query error");
+
+        gas.spent()
+    };
+
+    let default = Schedule::default();
+    let expensive = Schedule {
+        call_base_cost: default.call_base_cost + 1_000,
+        instantiate_base_cost: default.instantiate_base_cost + 500,
+        ..Schedule::default()
+    };
+
+    assert_eq!(spent_with(expensive) - spent_with(default), 1_500);
+}
+
+#[test]
+fn schedule_selected_by_block_height() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let mut network = NetworkState::with_block_height(99);
+
+    network.register_schedule(
+        50,
+        Schedule {
+            version: 1,
+            ..Schedule::default()
+        },
+    )?;
+
+    assert_eq!(network.schedule().version, 1);
+
+    let contract = Contract::new(Counter::new(99), code.to_vec());
+    let code_hash = *contract.code_hash();
+    let contract_id = network.deploy(contract)?;
+
+    assert_eq!(network.get_code(&code_hash)?.schedule_version(), 1);
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    assert_eq!(
+        network.query::<_, i32>(contract_id, counter::READ_VALUE, &mut gas)?,
+        99
+    );
+
+    // Versions must increase with the activation height
+    assert!(matches!(
+        network.register_schedule(70, Schedule::default()),
+        Err(VMError::InvalidSchedule)
+    ));
+
+    // and cannot be registered twice, not even to replace a schedule
+    let replacement = Schedule {
+        version: 1,
+        call_base_cost: 1,
+        ..Schedule::default()
+    };
+
+    assert!(matches!(
+        network.register_schedule(50, replacement),
+        Err(VMError::InvalidSchedule)
+    ));
+    assert_eq!(
+        network.schedule().call_base_cost,
+        Schedule::default().call_base_cost
+    );
+
+    // A new schedule activated by a hard fork makes the contract outdated,
+    // it is re-instrumented from its original code in memory when called,
+    // leaving the stored code untouched
+    network.register_schedule(
+        90,
+        Schedule {
+            version: 2,
+            ..Schedule::default()
+        },
+    )?;
+
+    assert_eq!(
+        network.query::<_, i32>(contract_id, counter::READ_VALUE, &mut gas)?,
+        99
+    );
+    assert_eq!(network.get_code(&code_hash)?.schedule_version(), 1);
+
+    // The stored code is re-instrumented explicitly
+    network.reinstrument(&contract_id)?;
+
+    let code_entry = network.get_code(&code_hash)?;
+    assert_eq!(code_entry.schedule_version(), 2);
+    assert_eq!(code_entry.bytecode(), &code[..]);
+    assert_ne!(code_entry.instrumented_bytecode(), &code[..]);
+    drop(code_entry);
+
+    // or all at once
+    let fibonacci_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/fibonacci.wasm"
+    );
+    let fibonacci = Contract::new(Fibonacci, fibonacci_code.to_vec());
+    let fibonacci_hash = *fibonacci.code_hash();
+    network.deploy(fibonacci)?;
+
+    network.register_schedule(
+        95,
+        Schedule {
+            version: 3,
+            ..Schedule::default()
+        },
+    )?;
+
+    network.reinstrument_all()?;
+
+    assert_eq!(network.get_code(&code_hash)?.schedule_version(), 3);
+    assert_eq!(network.get_code(&fibonacci_hash)?.schedule_version(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn events_are_returned_by_transact() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/events.wasm");
+
+    let mut network = NetworkState::default();
+    let contract_id =
+        network.deploy(Contract::new(Events::new(), code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let ((), emitted) = network.transact_with_events(
+        contract_id,
+        (events::EMIT, vec![1u8, 2, 3]),
+        &mut gas,
+    )?;
+
+    assert_eq!(emitted.len(), 1);
+    assert_eq!(emitted[0].contract(), &contract_id);
+    assert_eq!(emitted[0].topics(), &[events::TOPIC]);
+    assert_eq!(emitted[0].data(), &[1, 2, 3]);
+
+    // Events of a failed transaction are discarded along with its state
+    assert!(network
+        .transact_with_events::<_, ()>(
+            contract_id,
+            (events::EMIT_AND_PANIC, vec![4u8]),
+            &mut gas,
+        )
+        .is_err());
+
+    assert_eq!(
+        network.query::<_, u32>(contract_id, events::EMITTED, &mut gas)?,
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn transaction_receipts() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let delegator_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/delegator.wasm"
+    );
+    let snapshot_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/self_snapshot.wasm"
+    );
+
+    let mut network = NetworkState::default();
+
+    let counter_id = network
+        .deploy(Contract::new(Counter::new(99), counter_code.to_vec()))?;
+    let delegator_id =
+        network.deploy(Contract::new(Delegator, delegator_code.to_vec()))?;
+    let snapshot_id = network
+        .deploy(Contract::new(SelfSnapshot::new(7), snapshot_code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let receipt = network.transact_with_receipt::<_, ()>(
+        delegator_id,
+        (
+            delegator::DELEGATE_TRANSACTION,
+            counter_id,
+            counter::INCREMENT,
+        ),
+        &mut gas,
+    );
+
+    assert!(receipt.is_success());
+    assert_eq!(receipt.ret(), Some(&()));
+    assert_eq!(receipt.gas_spent(), gas.spent());
+
+    // The delegator called into the counter
+    assert_eq!(receipt.calls().len(), 1);
+    let call = &receipt.calls()[0];
+    assert_eq!(call.contract(), &delegator_id);
+    assert_eq!(call.kind(), CallKind::Transaction);
+    assert_eq!(call.calls().len(), 1);
+    assert_eq!(call.calls()[0].contract(), &counter_id);
+    assert!(call.calls()[0].gas_spent() < call.gas_spent());
+
+    // A panicking transaction reports the panic
+    let receipt = network.transact_with_receipt::<_, ()>(
+        snapshot_id,
+        (self_snapshot::UPDATE_AND_PANIC, 11),
+        &mut gas,
+    );
+
+    assert!(matches!(receipt.status(), TransactionStatus::Panic(_)));
+    assert_eq!(receipt.ret(), None);
+
+    // Other failures report the error raised by the host, rather than the
+    // traps it unwound through
+    let receipt = network.transact_with_receipt::<_, ()>(
+        delegator_id,
+        (
+            delegator::DELEGATE_TRANSACTION,
+            ContractId::reserved(0x42),
+            counter::INCREMENT,
+        ),
+        &mut gas,
+    );
+
+    assert_eq!(
+        receipt.status(),
+        &TransactionStatus::Error(VMError::UnknownContract.to_string())
+    );
+
+    // A transaction running out of gas reports it
+    let mut gas = GasMeter::with_limit(1);
+
+    let receipt = network.transact_with_receipt::<_, ()>(
+        counter_id,
+        counter::INCREMENT,
+        &mut gas,
+    );
+
+    assert_eq!(receipt.status(), &TransactionStatus::OutOfGas);
+    assert_eq!(receipt.gas_spent(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn out_of_bounds_memory_access_traps() -> Result<(), VMError> {
+    let code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/out_of_bounds.wasm"
+    );
+
+    let mut network = NetworkState::default();
+    let contract_id =
+        network.deploy(Contract::new(OutOfBounds, code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let traps = |result: Result<(), VMError>| {
+        matches!(
+            result.as_ref().map_err(VMError::root_cause),
+            Err(VMError::MemoryAccessOutOfBounds)
+        )
+    };
+
+    let queries = [
+        out_of_bounds::DEBUG,
+        out_of_bounds::CALLEE,
+        out_of_bounds::SIG,
+        out_of_bounds::GET,
+        out_of_bounds::PUT,
+        out_of_bounds::HASH,
+        out_of_bounds::QUERY,
+    ];
+
+    for ofs in &[out_of_bounds::PAST_THE_END, out_of_bounds::OVERFLOWING] {
+        for query in &queries {
+            assert!(traps(network.query::<_, ()>(
+                contract_id,
+                (*query, *ofs),
+                &mut gas
+            )));
+        }
+
+        for transaction in &[out_of_bounds::EMIT, out_of_bounds::TRANSACT] {
+            assert!(traps(network.transact::<_, ()>(
+                contract_id,
+                (*transaction, *ofs),
+                &mut gas
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds a module importing a `fn()` host function
+fn module_importing(module: &str, field: &str) -> Vec<u8> {
+    let mut imports = vec![0x01];
+    imports.push(module.len() as u8);
+    imports.extend(module.as_bytes());
+    imports.push(field.len() as u8);
+    imports.extend(field.as_bytes());
+    // function import of type 0
+    imports.extend(&[0x00, 0x00]);
+
+    let mut code = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    // type section with a single `fn()` type
+    code.extend(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+    // import section
+    code.push(0x02);
+    code.push(imports.len() as u8);
+    code.extend(imports);
+
+    code
+}
+
+#[test]
+fn deploy_fails_with_invalid_imports() {
+    let mut network = NetworkState::default();
+
+    let mut deploy = |module, field| {
+        network.deploy(Contract::new(0u8, module_importing(module, field)))
+    };
+
+    assert!(matches!(
+        deploy("env", "not_in_abi"),
+        Err(VMError::InvalidImport { field, .. }) if field == "not_in_abi"
+    ));
+
+    assert!(matches!(
+        deploy("wasi", "debug"),
+        Err(VMError::InvalidImport { module, .. }) if module == "wasi"
+    ));
+
+    // `debug` takes a pointer and a length
+    assert!(matches!(
+        deploy("env", "debug"),
+        Err(VMError::InvalidImport { expected, found, .. })
+            if expected == "fn(I32, I32)" && found == "fn()"
+    ));
+}
+
+#[test]
+fn checkpoints() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let mut network = NetworkState::default();
+    let counter_id =
+        network.deploy(Contract::new(Counter::new(99), code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let read = |network: &mut NetworkState, gas: &mut GasMeter| {
+        network.query::<_, i32>(counter_id, counter::READ_VALUE, gas)
+    };
+
+    network.begin_checkpoint();
+    network.transact::<_, ()>(counter_id, counter::INCREMENT, &mut gas)?;
+    assert_eq!(read(&mut network, &mut gas)?, 100);
+    network.revert_checkpoint()?;
+    assert_eq!(read(&mut network, &mut gas)?, 99);
+
+    // Changes committed in a nested checkpoint are reverted with the
+    // enclosing one
+    network.begin_checkpoint();
+    network.begin_checkpoint();
+    network.transact::<_, ()>(counter_id, counter::INCREMENT, &mut gas)?;
+    network.commit_checkpoint();
+    assert_eq!(read(&mut network, &mut gas)?, 100);
+    network.revert_checkpoint()?;
+    assert_eq!(read(&mut network, &mut gas)?, 99);
+
+    // Contracts deployed within a reverted checkpoint are removed
+    network.begin_checkpoint();
+    let fibonacci_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/fibonacci.wasm"
+    );
+    let fibonacci_id =
+        network.deploy(Contract::new(Fibonacci, fibonacci_code.to_vec()))?;
+    network.revert_checkpoint()?;
+    assert!(matches!(
+        network.get_contract(&fibonacci_id),
+        Err(VMError::UnknownContract)
+    ));
+
+    // and the nonce their address was derived from is reused
+    assert_eq!(
+        network.deploy(Contract::new(Fibonacci, fibonacci_code.to_vec()))?,
+        fibonacci_id
+    );
+
+    network.begin_checkpoint();
+    network.transact::<_, ()>(counter_id, counter::INCREMENT, &mut gas)?;
+    network.commit_checkpoint();
+    assert_eq!(read(&mut network, &mut gas)?, 100);
+
+    Ok(())
+}
+
+#[test]
+fn try_calls_catch_failing_sub_calls() -> Result<(), VMError> {
+    let snapshot_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/self_snapshot.wasm"
+    );
+    let try_caller_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/try_caller.wasm"
+    );
+
+    let mut network = NetworkState::default();
+
+    let snapshot_id = network
+        .deploy(Contract::new(SelfSnapshot::new(7), snapshot_code.to_vec()))?;
+    let try_caller_id = network
+        .deploy(Contract::new(TryCaller::new(), try_caller_code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    const GAS_LIMIT: u64 = 100_000_000;
+
+    // The panic is caught by the caller, and the callee's changes reverted
+    let transaction =
+        Transaction::from_canon(&(self_snapshot::UPDATE_AND_PANIC, 11));
+
+    assert_eq!(
+        network.transact::<_, i32>(
+            try_caller_id,
+            (
+                try_caller::TRY_TRANSACT,
+                snapshot_id,
+                GAS_LIMIT,
+                transaction
+            ),
+            &mut gas,
+        )?,
+        CALL_PANICKED
+    );
+
+    assert_eq!(
+        network.query::<_, i32>(try_caller_id, try_caller::STATUS, &mut gas)?,
+        CALL_PANICKED
+    );
+
+    assert_eq!(
+        network.query::<_, i32>(
+            snapshot_id,
+            self_snapshot::CROSSOVER,
+            &mut gas
+        )?,
+        7
+    );
+
+    let transaction =
+        Transaction::from_canon(&(self_snapshot::SET_CROSSOVER, 9));
+
+    assert_eq!(
+        network.transact::<_, i32>(
+            try_caller_id,
+            (
+                try_caller::TRY_TRANSACT,
+                snapshot_id,
+                GAS_LIMIT,
+                transaction
+            ),
+            &mut gas,
+        )?,
+        CALL_SUCCEEDED
+    );
+
+    assert_eq!(
+        network.query::<_, i32>(
+            snapshot_id,
+            self_snapshot::CROSSOVER,
+            &mut gas
+        )?,
+        9
+    );
+
+    let query = Query::from_canon(&self_snapshot::CROSSOVER);
+
+    assert_eq!(
+        network.query::<_, i32>(
+            try_caller_id,
+            (
+                try_caller::TRY_QUERY,
+                ContractId::reserved(0x11),
+                GAS_LIMIT,
+                query,
+            ),
+            &mut gas,
+        )?,
+        CALL_FAILED
+    );
+
+    Ok(())
+}
+
+#[test]
+fn try_calls_respect_gas_limits() -> Result<(), VMError> {
+    let snapshot_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/self_snapshot.wasm"
+    );
+    let try_caller_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/try_caller.wasm"
+    );
+
+    let mut network = NetworkState::default();
+
+    let snapshot_id = network
+        .deploy(Contract::new(SelfSnapshot::new(7), snapshot_code.to_vec()))?;
+    let try_caller_id = network
+        .deploy(Contract::new(TryCaller::new(), try_caller_code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    // The sub-call runs out of gas, without exhausting the caller's gas
+    let transaction =
+        Transaction::from_canon(&(self_snapshot::SET_CROSSOVER, 9));
+
+    assert_eq!(
+        network.transact::<_, i32>(
+            try_caller_id,
+            (try_caller::TRY_TRANSACT, snapshot_id, 1u64, transaction),
+            &mut gas,
+        )?,
+        CALL_OUT_OF_GAS
+    );
+
+    assert!(gas.left() > 0);
+
+    assert_eq!(
+        network.query::<_, i32>(
+            snapshot_id,
+            self_snapshot::CROSSOVER,
+            &mut gas
+        )?,
+        7
+    );
+
+    Ok(())
+}
+
+#[test]
+fn nested_calls_leave_a_gas_reserve_to_the_caller() -> Result<(), VMError> {
+    let fibonacci_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/fibonacci.wasm"
+    );
+    let delegator_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/delegator.wasm"
+    );
+
+    let mut network = NetworkState::default();
+
+    let fibonacci_id =
+        network.deploy(Contract::new(Fibonacci, fibonacci_code.to_vec()))?;
+    let delegator_id =
+        network.deploy(Contract::new(Delegator, delegator_code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000);
+
+    // The callee runs out of gas, but cannot spend the reserve of the caller
+    let result = network.query::<_, u64>(
+        delegator_id,
+        (
+            delegator::DELEGATE_QUERY,
+            fibonacci_id,
+            (fibonacci::COMPUTE, 40u64),
+        ),
+        &mut gas,
+    );
+
+    assert_eq!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(&VMError::OutOfGas)
+    );
+
+    assert!(gas.left() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn store_calls_are_charged_by_byte() {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/storage.wasm");
+
+    let spent_with = |schedule: Schedule, transaction: u8| {
+        let contract = Contract::new(Storage, code.to_vec());
+
+        let mut network = NetworkState::default().with_schedule(schedule);
+        let contract_id = network.deploy(contract).expect("Deploy error");
+
+        let mut gas = GasMeter::with_limit(1_000_000_000);
+
+        network
+            .transact::<_, ()>(
+                contract_id,
+                (transaction, vec![0xffu8; 100]),
+                &mut gas,
+            )
+            .expect("Transaction error");
+
+        gas.spent()
+    };
+
+    let default = Schedule::default();
+    let expensive = || Schedule {
+        store_base_cost: default.store_base_cost + 100,
+        store_put_per_byte_cost: default.store_put_per_byte_cost + 10,
+        hash_per_byte_cost: default.hash_per_byte_cost + 5,
+        ..Schedule::default()
+    };
+
+    assert_eq!(
+        spent_with(expensive(), storage::PUT)
+            - spent_with(Schedule::default(), storage::PUT),
+        100 + 100 * 10 + 100 * 5
+    );
+
+    assert_eq!(
+        spent_with(expensive(), storage::HASH)
+            - spent_with(Schedule::default(), storage::HASH),
+        100 + 100 * 5
+    );
+}
+
+#[test]
+fn gas_estimation() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let delegator_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/delegator.wasm"
+    );
+
+    let mut network = NetworkState::default();
+
+    let counter_id = network
+        .deploy(Contract::new(Counter::new(99), counter_code.to_vec()))?;
+    let delegator_id =
+        network.deploy(Contract::new(Delegator, delegator_code.to_vec()))?;
+
+    let transaction = (
+        delegator::DELEGATE_TRANSACTION,
+        counter_id,
+        counter::INCREMENT,
+    );
+
+    let estimate = network.estimate_gas::<_, ()>(delegator_id, transaction);
+
+    assert!(estimate.result().is_ok());
+    assert!(estimate.peak_nested_gas() > 0);
+    assert!(estimate.peak_nested_gas() < estimate.gas_spent());
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    // The estimate leaves the state untouched
+    assert_eq!(
+        network.query::<_, i32>(counter_id, counter::READ_VALUE, &mut gas)?,
+        99
+    );
+
+    // The required limit is the lowest one the transaction succeeds with
+    let mut gas = GasMeter::with_limit(estimate.required_limit() - 1);
+
+    let result = network.transact::<_, ()>(delegator_id, transaction, &mut gas);
+    assert!(matches!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(VMError::OutOfGas)
+    ));
+
+    let mut gas = GasMeter::with_limit(estimate.required_limit());
+
+    network.transact::<_, ()>(delegator_id, transaction, &mut gas)?;
+    assert_eq!(gas.spent(), estimate.gas_spent());
+
+    // Failures are reported
+    let estimate = network.estimate_gas::<_, ()>(counter_id, 0xffu8);
+    assert!(estimate.result().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn queries_cannot_mutate_state() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let delegator_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/delegator.wasm"
+    );
+
+    let mut network = NetworkState::default();
+
+    let counter_id = network
+        .deploy(Contract::new(Counter::new(99), counter_code.to_vec()))?;
+    let delegator_id =
+        network.deploy(Contract::new(Delegator, delegator_code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let result = network.query::<_, ()>(
+        delegator_id,
+        (
+            delegator::DELEGATE_TRANSACTION_IN_QUERY,
+            counter_id,
+            Transaction::from_canon(&counter::INCREMENT),
+        ),
+        &mut gas,
+    );
+
+    assert!(matches!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(VMError::StateMutationInQuery)
+    ));
+
+    assert_eq!(
+        network.query::<_, i32>(counter_id, counter::READ_VALUE, &mut gas)?,
+        99
+    );
+
+    Ok(())
+}
+
+#[test]
+fn call_depth_is_limited() -> Result<(), VMError> {
+    let code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/self_snapshot.wasm"
+    );
+
+    let mut network = NetworkState::default().with_schedule(Schedule {
+        max_call_depth: 2,
+        ..Schedule::default()
+    });
+
+    let contract_id =
+        network.deploy(Contract::new(SelfSnapshot::new(7), code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    // Wraps the transaction in a call of the contract to itself
+    let nested = |transaction: Transaction| {
+        Transaction::from_canon(&(
+            self_snapshot::SELF_CALL_TEST_B,
+            contract_id,
+            transaction,
+        ))
+    };
+
+    let set_crossover =
+        || Transaction::from_canon(&(self_snapshot::SET_CROSSOVER, 12));
+
+    // Two calls deep
+    network.transact::<_, ()>(
+        contract_id,
+        (
+            self_snapshot::SELF_CALL_TEST_B,
+            contract_id,
+            set_crossover(),
+        ),
+        &mut gas,
+    )?;
+
+    // Three calls deep
+    let result = network.transact::<_, ()>(
+        contract_id,
+        (
+            self_snapshot::SELF_CALL_TEST_B,
+            contract_id,
+            nested(set_crossover()),
+        ),
+        &mut gas,
+    );
+
+    assert!(matches!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(VMError::CallDepthExceeded)
+    ));
+
+    Ok(())
+}
+
+/// Builds a module declaring a memory with the given limits, in pages
+fn module_with_memory(initial: u8, maximum: Option<u8>) -> Vec<u8> {
+    let limits = match maximum {
+        Some(maximum) => vec![0x01, initial, maximum],
+        None => vec![0x00, initial],
+    };
+
+    let mut code = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    // memory section with a single memory
+    code.push(0x05);
+    code.push(limits.len() as u8 + 1);
+    code.push(0x01);
+    code.extend(limits);
+
+    code
+}
+
+#[test]
+fn deploy_enforces_max_memory_pages() -> Result<(), VMError> {
+    let mut network = NetworkState::default().with_schedule(Schedule {
+        max_memory_pages: 16,
+        ..Schedule::default()
+    });
+
+    let mut deploy = |initial, maximum| {
+        network.deploy(Contract::new(0u8, module_with_memory(initial, maximum)))
+    };
+
+    let too_large = |result: Result<ContractId, VMError>| {
+        matches!(
+            result,
+            Err(VMError::InstrumentalizationError(
+                InstrumentalizationError::MaxMemoryPages
+            ))
+        )
+    };
+
+    assert!(deploy(4, Some(16)).is_ok());
+    assert!(too_large(deploy(17, None)));
+    assert!(too_large(deploy(4, Some(17))));
+
+    // The maximum is declared when missing, capping growth at runtime
+    let contract_id = deploy(4, None)?;
+    let code_hash = *network.get_contract(&contract_id)?.code_hash();
+    let code = network.get_code(&code_hash)?;
+
+    let module =
+        parity_wasm::deserialize_buffer::<parity_wasm::elements::Module>(
+            code.instrumented_bytecode(),
+        )
+        .expect("Invalid module");
+
+    let memory = &module.memory_section().expect("No memory").entries()[0];
+    assert_eq!(memory.limits().maximum(), Some(16));
+
+    Ok(())
+}
+
+#[test]
+fn deploy_enforces_code_limits() {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let deploy = |schedule: Schedule| {
+        let mut network = NetworkState::default().with_schedule(schedule);
+        network.deploy(Contract::new(Counter::new(99), code.to_vec()))
+    };
+
+    let rejected = |schedule: Schedule, error: InstrumentalizationError| {
+        matches!(
+            deploy(schedule),
+            Err(VMError::InstrumentalizationError(e)) if e == error
+        )
+    };
+
+    assert!(deploy(Schedule::default()).is_ok());
+
+    assert!(rejected(
+        Schedule {
+            max_code_size: code.len() as u32 - 1,
+            ..Schedule::default()
+        },
+        InstrumentalizationError::MaxCodeSize
+    ));
+
+    assert!(rejected(
+        Schedule {
+            max_functions: 1,
+            ..Schedule::default()
+        },
+        InstrumentalizationError::MaxFunctions
+    ));
+
+    assert!(rejected(
+        Schedule {
+            max_exports: 1,
+            ..Schedule::default()
+        },
+        InstrumentalizationError::MaxExports
+    ));
+}
+
+#[test]
+fn deploy_with_gas_charges_code_bytes() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let mut network = NetworkState::default();
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    network.deploy_with_gas(
+        Contract::new(Counter::new(99), code.to_vec()),
+        &mut gas,
+    )?;
+
+    let per_byte = Schedule::default().put_code_per_byte_cost;
+    assert_eq!(gas.spent(), code.len() as u64 * per_byte);
+
+    // Code already stored is not charged again
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    network.deploy_with_gas(
+        Contract::new(Counter::new(100), code.to_vec()),
+        &mut gas,
+    )?;
+
+    assert_eq!(gas.spent(), 0);
+
+    // Nothing is deployed when running out of gas
+    let fibonacci_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/fibonacci.wasm"
+    );
+    let fibonacci = Contract::new(Fibonacci, fibonacci_code.to_vec());
+    let fibonacci_hash = *fibonacci.code_hash();
+
+    let mut gas = GasMeter::with_limit(10);
+    let result = network.deploy_with_gas(fibonacci, &mut gas);
+
+    assert!(matches!(result, Err(VMError::OutOfGas)));
+    assert!(network.get_code(&fibonacci_hash).is_err());
+
+    // Code put into the state by an upgrade is charged as well
+    let counter_v2_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/counter_v2.wasm"
+    );
+    let id = network.deploy(Contract::new(Counter::new(1), code.to_vec()))?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    network.upgrade(id, counter_v2_code.to_vec(), &mut gas)?;
+
+    assert!(gas.spent() >= counter_v2_code.len() as u64 * per_byte);
+
+    Ok(())
+}
+
 #[test]
 fn deploy_fails_with_floats() {
     let counter = CounterFloat::new(9.99f32);
 
     let code = include_bytes!(
-        "../target/wasm32-unknown-unknown/release/counter_float.wasm"
+        "../target/wasm32-unknown-unknown/release/counter_float.wasm"
+    );
+
+    let contract = Contract::new(counter, code.to_vec());
+
+    let mut network = NetworkState::default();
+
+    assert!(matches!(
+        network.deploy(contract),
+        Err(rusk_vm::VMError::InstrumentalizationError(_))
+    ));
+}
+
+#[test]
+fn deploy_policy() -> Result<(), VMError> {
+    let code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/counter_float.wasm"
+    );
+
+    let contract = Contract::new(CounterFloat::new(9.99f32), code.to_vec());
+
+    let policy = DeployPolicy::default().with_forbidden_floats(false);
+    let mut network = NetworkState::default().with_deploy_policy(policy);
+
+    let contract_id = network.deploy(contract)?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    network.transact::<_, ()>(
+        contract_id,
+        counter_float::INCREMENT,
+        &mut gas,
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn deploy_with_id() -> Result<(), VMError> {
+    // Smallest valid WASM module possible so `deploy` won't raise a
+    // `InvalidByteCode` error
+    let code = 0x0000_0001_6D73_6100_u64.to_le_bytes();
+
+    // Create a contract with a simple state
+    let contract = Contract::new(0xfeed_u16, code.to_vec());
+
+    // Reserve a `ContractId`
+    let id = ContractId::reserved(0x10);
+
+    // Deploy with the id given
+    let mut network = NetworkState::default();
+
+    // The id is the same returned by the deploy function
+    assert_eq!(id, network.deploy_with_id(id, contract)?);
+
+    // Get the contract deployed using the same id, and verify the state is also
+    // the same
+    let state: u16 = network
+        .get_contract(&id)?
+        .state()
+        .cast()
+        .expect("Cannot cast the state");
+    assert_eq!(state, 0xfeed);
+
+    // Deploying another contract at the same address fails
+    let contract = Contract::new(0xcafe_u16, code.to_vec());
+    assert!(matches!(
+        network.deploy_with_id(id, contract),
+        Err(VMError::ContractAlreadyExists(existing)) if existing == id
+    ));
+
+    // and leaves the contract already deployed untouched
+    let state: u16 = network
+        .get_contract(&id)?
+        .state()
+        .cast()
+        .expect("Cannot cast the state");
+    assert_eq!(state, 0xfeed);
+
+    Ok(())
+}
+
+#[test]
+fn deploy_derives_distinct_ids() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    // The same code deployed twice creates two contracts
+    let contract = Contract::new(Counter::new(1), code.to_vec());
+    let code_hash = *contract.code_hash();
+
+    let first = network.deploy(contract)?;
+    let second =
+        network.deploy(Contract::new(Counter::new(2), code.to_vec()))?;
+
+    assert_ne!(first, second);
+    assert_eq!(
+        first,
+        Contract::derive_id(&NetworkState::DEPLOYER, 0, &code_hash)
+    );
+    assert_eq!(
+        second,
+        Contract::derive_id(&NetworkState::DEPLOYER, 1, &code_hash)
+    );
+
+    assert_eq!(
+        network.query::<_, i32>(first, counter::READ_VALUE, &mut gas)?,
+        1
+    );
+    assert_eq!(
+        network.query::<_, i32>(second, counter::READ_VALUE, &mut gas)?,
+        2
+    );
+
+    // A salt makes the address known in advance
+    let salt = [7u8; 32];
+    let salted = network.deploy_with_salt(
+        Contract::new(Counter::new(3), code.to_vec()),
+        salt,
+    )?;
+
+    assert_eq!(
+        salted,
+        Contract::derive_id_with_salt(
+            &NetworkState::DEPLOYER,
+            &salt,
+            &code_hash
+        )
+    );
+
+    // but can be used only once for the same code
+    assert!(matches!(
+        network.deploy_with_salt(
+            Contract::new(Counter::new(4), code.to_vec()),
+            salt
+        ),
+        Err(VMError::ContractAlreadyExists(existing)) if existing == salted
+    ));
+
+    assert_eq!(
+        network.query::<_, i32>(salted, counter::READ_VALUE, &mut gas)?,
+        3
+    );
+
+    Ok(())
+}
+
+#[test]
+fn code_is_shared_between_contracts() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let contract = Contract::new(Counter::new(99), code.to_vec());
+    let code_hash = *contract.code_hash();
+
+    let first = network.deploy(contract)?;
+    let second = network.deploy_from_code_hash(code_hash, Counter::new(5))?;
+
+    assert_ne!(first, second);
+    assert_eq!(network.get_contract(&first)?.code_hash(), &code_hash);
+    assert_eq!(network.get_contract(&second)?.code_hash(), &code_hash);
+    assert_eq!(network.get_code(&code_hash)?.bytecode(), &code[..]);
+
+    // Contracts sharing code keep their own state
+    network.transact::<_, ()>(second, counter::INCREMENT, &mut gas)?;
+
+    assert_eq!(
+        network.query::<_, i32>(first, counter::READ_VALUE, &mut gas)?,
+        99
+    );
+    assert_eq!(
+        network.query::<_, i32>(second, counter::READ_VALUE, &mut gas)?,
+        6
+    );
+
+    assert!(matches!(
+        network.deploy_from_code_hash([0u8; 32], Counter::new(5)),
+        Err(VMError::UnknownCode)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn contracts_deploy_contracts() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let factory_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/factory.wasm");
+
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let factory_id =
+        network.deploy(Contract::new(Factory::new(), factory_code.to_vec()))?;
+
+    let counter = Contract::new(Counter::new(99), counter_code.to_vec());
+    let code_hash = *counter.code_hash();
+    network.deploy(counter)?;
+
+    let initial_state = || ContractState::from_canon(&Counter::new(5));
+
+    // The factory instantiates the stored code at an address derived from
+    // its own id and nonce
+    let deployed = network.transact::<_, ContractId>(
+        factory_id,
+        (factory::DEPLOY_FROM_CODE_HASH, code_hash, initial_state()),
+        &mut gas,
+    )?;
+
+    assert_eq!(deployed, Contract::derive_id(&factory_id, 0, &code_hash));
+    assert_eq!(network.get_contract(&factory_id)?.nonce(), 1);
+
+    network.transact::<_, ()>(deployed, counter::INCREMENT, &mut gas)?;
+    assert_eq!(
+        network.query::<_, i32>(deployed, counter::READ_VALUE, &mut gas)?,
+        6
+    );
+
+    // Contracts deployed by a failed transaction are reverted with it
+    let result = network.transact::<_, ContractId>(
+        factory_id,
+        (factory::DEPLOY_AND_PANIC, code_hash, initial_state()),
+        &mut gas,
+    );
+
+    assert!(result.is_err());
+    assert_eq!(network.get_contract(&factory_id)?.nonce(), 1);
+    assert!(matches!(
+        network.get_contract(&Contract::derive_id(&factory_id, 1, &code_hash)),
+        Err(VMError::UnknownContract)
+    ));
+
+    // Queries cannot deploy contracts
+    let result = network.query::<_, ContractId>(
+        factory_id,
+        (factory::DEPLOY_IN_QUERY, code_hash, initial_state()),
+        &mut gas,
+    );
+
+    assert!(matches!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(VMError::StateMutationInQuery)
+    ));
+
+    // The factory can deploy new code as well
+    let code = 0x0000_0001_6D73_6100_u64.to_le_bytes().to_vec();
+
+    let deployed = network.transact::<_, ContractId>(
+        factory_id,
+        (factory::DEPLOY, code.clone(), initial_state()),
+        &mut gas,
+    )?;
+
+    let code_hash = *network.get_contract(&deployed)?.code_hash();
+    assert_eq!(network.get_code(&code_hash)?.bytecode(), &code[..]);
+    assert_eq!(deployed, Contract::derive_id(&factory_id, 1, &code_hash));
+
+    assert_eq!(
+        network.query::<_, u32>(factory_id, factory::DEPLOYED, &mut gas)?,
+        2
+    );
+
+    // Code registered by a failed transaction is reverted with it, here a
+    // module with an empty custom section
+    let mut code = code;
+    code.extend_from_slice(&[0x00, 0x02, 0x01, b'x']);
+    let code_hash = *Contract::new((), code.clone()).code_hash();
+
+    let result = network.transact::<_, ContractId>(
+        factory_id,
+        (factory::DEPLOY_CODE_AND_PANIC, code, initial_state()),
+        &mut gas,
+    );
+
+    assert!(result.is_err());
+    assert!(matches!(
+        network.get_code(&code_hash),
+        Err(VMError::UnknownCode)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn contract_upgrades() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let v2_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/counter_v2.wasm"
+    );
+
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    // The host can upgrade any contract, its state is migrated by the new code
+    let counter_id = network
+        .deploy(Contract::new(Counter::new(99), counter_code.to_vec()))?;
+    let counter_hash = *network.get_contract(&counter_id)?.code_hash();
+
+    network.upgrade(counter_id, v2_code.to_vec(), &mut gas)?;
+
+    let v2_hash = *network.get_contract(&counter_id)?.code_hash();
+    assert_eq!(network.get_code(&v2_hash)?.bytecode(), &v2_code[..]);
+
+    // The old code is removed, since no other contract runs it
+    assert!(matches!(
+        network.get_code(&counter_hash),
+        Err(VMError::UnknownCode)
+    ));
+
+    network.transact::<_, ()>(counter_id, counter_v2::INCREMENT, &mut gas)?;
+    assert_eq!(
+        network.query::<_, i32>(
+            counter_id,
+            counter_v2::READ_VALUE,
+            &mut gas
+        )?,
+        991
+    );
+
+    // Contracts can upgrade the contracts they administer
+    let admin_id =
+        network.deploy(Contract::new(CounterV2::new(3), v2_code.to_vec()))?;
+    let managed_id = network.deploy(
+        Contract::new(Counter::new(7), counter_code.to_vec())
+            .with_admin(admin_id),
+    )?;
+
+    network.transact::<_, ()>(
+        admin_id,
+        (counter_v2::UPGRADE, managed_id, v2_hash),
+        &mut gas,
+    )?;
+
+    assert_eq!(network.get_contract(&managed_id)?.code_hash(), &v2_hash);
+    assert_eq!(
+        network.query::<_, i32>(
+            managed_id,
+            counter_v2::READ_VALUE,
+            &mut gas
+        )?,
+        70
+    );
+
+    // but not any other contract
+    let other_id = network
+        .deploy(Contract::new(Counter::new(7), counter_code.to_vec()))?;
+
+    let result = network.transact::<_, ()>(
+        admin_id,
+        (counter_v2::UPGRADE, other_id, v2_hash),
+        &mut gas,
+    );
+
+    assert!(matches!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(VMError::UnauthorizedUpgrade)
+    ));
+    assert_eq!(network.get_contract(&other_id)?.code_hash(), &counter_hash);
+
+    // A contract upgrading itself is migrated once the transaction returns,
+    // so the state it returns is migrated as well
+    network.transact::<_, ()>(
+        admin_id,
+        (counter_v2::UPGRADE, admin_id, v2_hash),
+        &mut gas,
+    )?;
+
+    assert_eq!(
+        network.query::<_, i32>(admin_id, counter_v2::READ_VALUE, &mut gas)?,
+        30
+    );
+
+    Ok(())
+}
+
+#[test]
+fn contracts_are_removed() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let destructible_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/destructible.wasm"
+    );
+    let try_caller_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/try_caller.wasm"
+    );
+
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    // The host can remove any contract, crediting its balance to a
+    // beneficiary
+    let beneficiary_id = network
+        .deploy(Contract::new(Counter::new(0), counter_code.to_vec()))?;
+
+    let counter = Contract::new(Counter::new(99), counter_code.to_vec());
+    let counter_hash = *counter.code_hash();
+    let counter_id = network.deploy(counter)?;
+    network.mint(&counter_id, 10)?;
+
+    assert_eq!(network.get_code(&counter_hash)?.references(), 2);
+
+    // but not in favor of itself
+    assert!(matches!(
+        network.remove_contract(&counter_id, &counter_id),
+        Err(VMError::InvalidBeneficiary)
+    ));
+
+    network.remove_contract(&counter_id, &beneficiary_id)?;
+
+    assert!(matches!(
+        network.get_contract(&counter_id),
+        Err(VMError::UnknownContract)
+    ));
+    assert!(matches!(
+        network.remove_contract(&counter_id, &beneficiary_id),
+        Err(VMError::UnknownContract)
+    ));
+    assert_eq!(network.get_contract(&beneficiary_id)?.balance(), 10);
+
+    // The code is kept as long as other contracts run it
+    assert_eq!(network.get_code(&counter_hash)?.references(), 1);
+
+    // Contracts can destruct themselves
+    let mut deploy_destructible = || {
+        network.deploy(Contract::new(Destructible, destructible_code.to_vec()))
+    };
+
+    let destructible_id = deploy_destructible()?;
+    let other_id = deploy_destructible()?;
+
+    network.transact::<_, ()>(
+        destructible_id,
+        (destructible::SELF_DESTRUCT, beneficiary_id),
+        &mut gas,
+    )?;
+
+    assert!(matches!(
+        network.get_contract(&destructible_id),
+        Err(VMError::UnknownContract)
+    ));
+
+    // but only if the transaction succeeds
+    let result = network.transact::<_, ()>(
+        other_id,
+        (destructible::SELF_DESTRUCT_AND_PANIC, beneficiary_id),
+        &mut gas,
+    );
+
+    assert!(result.is_err());
+    assert!(network.get_contract(&other_id).is_ok());
+
+    // or if the call destructing the contract does
+    let try_caller_id = network
+        .deploy(Contract::new(TryCaller::new(), try_caller_code.to_vec()))?;
+
+    assert_eq!(
+        network.transact::<_, i32>(
+            try_caller_id,
+            (
+                try_caller::TRY_TRANSACT,
+                other_id,
+                100_000_000u64,
+                Transaction::from_canon(&(
+                    destructible::SELF_DESTRUCT_AND_PANIC,
+                    beneficiary_id
+                ))
+            ),
+            &mut gas,
+        )?,
+        CALL_PANICKED
+    );
+
+    assert!(network.get_contract(&other_id).is_ok());
+
+    // Contracts destructing themselves while being migrated are removed once
+    // the upgrade succeeds, here the old state is the beneficiary
+    let migrated_id =
+        network.deploy(Contract::new(beneficiary_id, counter_code.to_vec()))?;
+    network.mint(&migrated_id, 5)?;
+
+    let events =
+        network.upgrade(migrated_id, destructible_code.to_vec(), &mut gas)?;
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].contract(), &migrated_id);
+    assert_eq!(events[0].topics(), &[destructible::TOPIC]);
+    assert_eq!(events[0].data(), beneficiary_id.as_bytes());
+
+    assert!(matches!(
+        network.get_contract(&migrated_id),
+        Err(VMError::UnknownContract)
+    ));
+    assert_eq!(network.get_contract(&beneficiary_id)?.balance(), 15);
+
+    // The code is removed along with the last contract running it
+    network.remove_contract(&beneficiary_id, &other_id)?;
+
+    assert!(matches!(
+        network.get_code(&counter_hash),
+        Err(VMError::UnknownCode)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn self_destruct_beneficiaries_are_checked() -> Result<(), VMError> {
+    let destructible_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/destructible.wasm"
+    );
+    let try_caller_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/try_caller.wasm"
+    );
+
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let mut deploy_destructible = || {
+        network.deploy(Contract::new(Destructible, destructible_code.to_vec()))
+    };
+
+    let a_id = deploy_destructible()?;
+    let b_id = deploy_destructible()?;
+    let c_id = deploy_destructible()?;
+
+    network.mint(&a_id, 3)?;
+    network.mint(&b_id, 4)?;
+
+    // A contract cannot destruct in favor of itself
+    let result = network.transact::<_, ()>(
+        a_id,
+        (destructible::SELF_DESTRUCT, a_id),
+        &mut gas,
+    );
+
+    assert_eq!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(&VMError::InvalidBeneficiary)
+    );
+
+    // and the failure can be caught by the caller, as well as the one of an
+    // unknown beneficiary
+    let try_caller_id = network
+        .deploy(Contract::new(TryCaller::new(), try_caller_code.to_vec()))?;
+
+    let mut try_transact = |transaction: Transaction| {
+        network.transact::<_, i32>(
+            try_caller_id,
+            (try_caller::TRY_TRANSACT, a_id, 100_000_000u64, transaction),
+            &mut gas,
+        )
+    };
+
+    assert_eq!(
+        try_transact(Transaction::from_canon(&(
+            destructible::SELF_DESTRUCT,
+            ContractId::reserved(0x42)
+        )))?,
+        CALL_FAILED
+    );
+
+    // A contract already destructed in the transaction cannot be the
+    // beneficiary either, so a contract cannot pay back its benefactor
+    assert_eq!(
+        try_transact(Transaction::from_canon(&(
+            destructible::SELF_DESTRUCT_AND_FORWARD,
+            b_id,
+            a_id
+        )))?,
+        CALL_FAILED
+    );
+
+    assert!(network.get_contract(&a_id).is_ok());
+    assert!(network.get_contract(&b_id).is_ok());
+
+    // but it can pass the balance on
+    network.transact::<_, ()>(
+        a_id,
+        (destructible::SELF_DESTRUCT_AND_FORWARD, b_id, c_id),
+        &mut gas,
+    )?;
+
+    assert!(matches!(
+        network.get_contract(&a_id),
+        Err(VMError::UnknownContract)
+    ));
+    assert!(matches!(
+        network.get_contract(&b_id),
+        Err(VMError::UnknownContract)
+    ));
+    assert_eq!(network.get_contract(&c_id)?.balance(), 7);
+
+    Ok(())
+}
+
+#[test]
+fn deploy_with_init() -> Result<(), VMError> {
+    let code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/initialized.wasm"
     );
 
-    let contract = Contract::new(counter, code.to_vec());
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let contract = || Contract::new(Initialized::default(), code.to_vec());
+    let code_hash = *contract().code_hash();
+
+    // The contract derives its initial state from the constructor arguments
+    let (id, _) = network.deploy_with_init(contract(), 42i32, &mut gas)?;
+
+    assert!(gas.spent() > 0);
+    assert_eq!(
+        network.query::<_, i32>(id, initialized::READ_VALUE, &mut gas)?,
+        42
+    );
+
+    // and can reject them, in which case the contract is not deployed
+    let result = network.deploy_with_init(contract(), -1i32, &mut gas);
+    assert!(result.is_err());
+
+    let (id, _) = network.deploy_with_init(contract(), 7i32, &mut gas)?;
+    assert_eq!(
+        id,
+        Contract::derive_id(&NetworkState::DEPLOYER, 1, &code_hash)
+    );
+
+    // A plain deploy doesn't run `init`
+    let id = network.deploy(contract())?;
+    assert!(!network.query::<_, bool>(
+        id,
+        initialized::IS_INITIALIZED,
+        &mut gas
+    )?);
+
+    // Contracts destructing themselves while being initialized are removed
+    // once deployed, the events emitted are returned
+    let destructible_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/destructible.wasm"
+    );
+
+    let (destructed_id, events) = network.deploy_with_init(
+        Contract::new(Destructible, destructible_code.to_vec()),
+        id,
+        &mut gas,
+    )?;
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].contract(), &destructed_id);
+    assert_eq!(events[0].topics(), &[destructible::TOPIC]);
+    assert_eq!(events[0].data(), id.as_bytes());
+
+    assert!(matches!(
+        network.get_contract(&destructed_id),
+        Err(VMError::UnknownContract)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn value_transfers() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/wallet.wasm");
+
+    let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    let alice = network.deploy(Contract::new(Wallet::new(), code.to_vec()))?;
+    let bob = network.deploy(Contract::new(Wallet::new(), code.to_vec()))?;
+    let carol = network.deploy(Contract::new(Wallet::new(), code.to_vec()))?;
+
+    let balance = |network: &NetworkState, id: &ContractId| {
+        network.get_contract(id).map(|contract| contract.balance())
+    };
+
+    let total_supply = |network: &NetworkState| -> Result<u64, VMError> {
+        Ok(balance(network, &alice)?
+            + balance(network, &bob)?
+            + balance(network, &carol)?)
+    };
+
+    // Value enters the state by being minted
+    network.mint(&carol, 110)?;
+    network.mint(&bob, 10)?;
+
+    assert_eq!(total_supply(&network)?, 120);
+
+    // The host moves value from a sender along with a transaction
+    assert_eq!(
+        network.transact_with_value::<_, u64>(
+            carol,
+            alice,
+            100,
+            wallet::RECEIVE,
+            &mut gas
+        )?,
+        100
+    );
+    assert_eq!(balance(&network, &alice)?, 100);
+    assert_eq!(balance(&network, &carol)?, 10);
+    assert_eq!(
+        network.query::<_, u64>(alice, wallet::BALANCE, &mut gas)?,
+        100
+    );
+    assert_eq!(total_supply(&network)?, 120);
+
+    // but no more than the balance of the sender
+    let result = network.transact_with_value::<_, u64>(
+        carol,
+        alice,
+        100,
+        wallet::RECEIVE,
+        &mut gas,
+    );
+
+    assert!(matches!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(VMError::NotEnoughFunds)
+    ));
+    assert_eq!(balance(&network, &carol)?, 10);
+    assert_eq!(total_supply(&network)?, 120);
+
+    // Contracts move value from their balance along with the calls they make
+    let forward = |value: u64, transaction: u8| {
+        (
+            wallet::FORWARD,
+            bob,
+            value,
+            Transaction::from_canon(&transaction),
+        )
+    };
+
+    network.transact::<_, u64>(
+        alice,
+        forward(30, wallet::RECEIVE),
+        &mut gas,
+    )?;
+
+    assert_eq!(balance(&network, &alice)?, 70);
+    assert_eq!(balance(&network, &bob)?, 40);
+    assert_eq!(
+        network.query::<_, u64>(bob, wallet::RECEIVED, &mut gas)?,
+        30
+    );
+    assert_eq!(total_supply(&network)?, 120);
+
+    // but no more than their balance
+    let result = network.transact::<_, u64>(
+        alice,
+        forward(1000, wallet::RECEIVE),
+        &mut gas,
+    );
+
+    assert!(matches!(
+        result.as_ref().map_err(VMError::root_cause),
+        Err(VMError::NotEnoughFunds)
+    ));
+    assert_eq!(balance(&network, &alice)?, 70);
+    assert_eq!(balance(&network, &bob)?, 40);
+    assert_eq!(total_supply(&network)?, 120);
+
+    // Transfers are reverted along with the calls that fail
+    let result = network.transact_with_value::<_, u64>(
+        carol,
+        bob,
+        5,
+        wallet::REFUSE,
+        &mut gas,
+    );
+
+    assert!(result.is_err());
+    assert_eq!(balance(&network, &bob)?, 40);
+    assert_eq!(balance(&network, &carol)?, 10);
+
+    let result =
+        network.transact::<_, u64>(alice, forward(5, wallet::REFUSE), &mut gas);
+
+    assert!(result.is_err());
+    assert_eq!(balance(&network, &alice)?, 70);
+    assert_eq!(balance(&network, &bob)?, 40);
+    assert_eq!(total_supply(&network)?, 120);
+
+    // Value leaves the state by being burned
+    network.burn(&alice, 70)?;
+
+    assert!(matches!(
+        network.burn(&alice, 1),
+        Err(VMError::NotEnoughFunds)
+    ));
+    assert_eq!(total_supply(&network)?, 50);
+
+    Ok(())
+}
+
+#[test]
+fn redeploy_keeps_the_deployed_code() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let block_height_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/block_height.wasm"
+    );
+
+    let id = ContractId::reserved(0x11);
 
     let mut network = NetworkState::default();
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+
+    network.deploy_with_id(
+        id,
+        Contract::new(Counter::new(7), counter_code.to_vec()),
+    )?;
+
+    // Populate the cache with the counter module
+    assert_eq!(
+        network.query::<_, i32>(id, counter::READ_VALUE, &mut gas)?,
+        7
+    );
 
+    // The code at an address cannot be replaced
     assert!(matches!(
-        network.deploy(contract),
-        Err(rusk_vm::VMError::InstrumentalizationError(_))
+        network.deploy_with_id(
+            id,
+            Contract::new(BlockHeight::new(), block_height_code.to_vec()),
+        ),
+        Err(VMError::ContractAlreadyExists(_))
     ));
+
+    assert_eq!(
+        network.query::<_, i32>(id, counter::READ_VALUE, &mut gas)?,
+        7
+    );
+
+    Ok(())
 }
 
 #[test]
-fn deploy_with_id() -> Result<(), VMError> {
-    // Smallest valid WASM module possible so `deploy` won't raise a
-    // `InvalidByteCode` error
-    let code = 0x0000_0001_6D73_6100_u64.to_le_bytes();
+fn module_cache_evicts_modules() -> Result<(), VMError> {
+    let counter_code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+    let block_height_code = include_bytes!(
+        "../target/wasm32-unknown-unknown/release/block_height.wasm"
+    );
 
-    // Create a contract with a simple state
-    let contract = Contract::new(0xfeed_u16, code.to_vec());
+    let mut network = NetworkState::default().with_module_cache_capacity(1);
+    let mut gas = GasMeter::with_limit(1_000_000_000);
 
-    // Reserve a `ContractId`
-    let id = ContractId::reserved(0x10);
+    let counter = network
+        .deploy(Contract::new(Counter::new(7), counter_code.to_vec()))?;
+    let block_height = network.deploy(Contract::new(
+        BlockHeight::new(),
+        block_height_code.to_vec(),
+    ))?;
+
+    // Each call evicts the module of the other contract
+    for _ in 0..2 {
+        assert_eq!(
+            network.query::<_, i32>(counter, counter::READ_VALUE, &mut gas)?,
+            7
+        );
+        assert_eq!(
+            network.query::<_, u64>(
+                block_height,
+                block_height::BLOCK_HEIGHT,
+                &mut gas
+            )?,
+            99
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn module_cache_is_not_shared_across_schedules() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
 
-    // Deploy with the id given
     let mut network = NetworkState::default();
 
-    // The id is the same returned by the deploy function
-    assert_eq!(id, network.deploy_with_id(id, contract)?);
+    let id = network.deploy(Contract::new(Counter::new(7), code.to_vec()))?;
 
-    // Get the contract deployed using the same id, and verify the state is also
-    // the same
-    let state: u16 = network
-        .get_contract(&id)?
-        .state()
-        .cast()
-        .expect("Cannot cast the state");
-    assert_eq!(state, 0xfeed);
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    network.query::<_, i32>(id, counter::READ_VALUE, &mut gas)?;
+    let spent = gas.spent();
+
+    // A clone running a pricier schedule must not be served the module
+    // instrumented under the original one
+    let mut pricier = network.clone();
+    pricier.register_schedule(
+        0,
+        Schedule {
+            version: 1,
+            regular_op_cost: 2,
+            ..Schedule::default()
+        },
+    )?;
 
-    // Deploy another contract at the same address
-    let contract = Contract::new(0xcafe_u16, code.to_vec());
-    network.deploy_with_id(id, contract)?;
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    pricier.query::<_, i32>(id, counter::READ_VALUE, &mut gas)?;
 
-    // Get the contract deployed using the same id, and verify the state is NOT
-    // the same as before.
-    //
-    // TODO: This means a contract CAN BE overriden once deployed, we need to
-    // decided if we should raise an error if a contract already exists with
-    // the same address
-    network.get_contract(&id)?;
+    assert!(gas.spent() > spent);
 
-    let state: u16 = network
-        .get_contract(&id)?
-        .state()
-        .cast()
-        .expect("Cannot cast the state");
-    assert_eq!(state, 0xcafe);
+    Ok(())
+}
+
+#[test]
+fn code_is_reinstrumented_under_another_deploy_policy() -> Result<(), VMError> {
+    let code =
+        include_bytes!("../target/wasm32-unknown-unknown/release/counter.wasm");
+
+    let mut network = NetworkState::default();
+
+    let contract = Contract::new(Counter::new(7), code.to_vec());
+    let code_hash = *contract.code_hash();
+    let id = network.deploy(contract)?;
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    network.query::<_, i32>(id, counter::READ_VALUE, &mut gas)?;
+    let spent = gas.spent();
+
+    // A clone without gas metering must neither be served the cached module
+    // nor the stored code instrumented under the original policy
+    let policy = DeployPolicy::default().with_metering(false);
+    let mut unmetered = network.clone().with_deploy_policy(policy);
+
+    let mut gas = GasMeter::with_limit(1_000_000_000);
+    unmetered.query::<_, i32>(id, counter::READ_VALUE, &mut gas)?;
+
+    assert!(gas.spent() < spent);
+
+    let default_id = DeployPolicy::default().id();
+    assert_ne!(policy.id(), default_id);
+    assert_eq!(unmetered.get_code(&code_hash)?.policy_id(), default_id);
+
+    // until the code is re-instrumented, even under the same schedule
+    unmetered.reinstrument(&id)?;
+    assert_eq!(unmetered.get_code(&code_hash)?.policy_id(), policy.id());
 
     Ok(())
 }
@@ -624,6 +2471,15 @@ fn persistence() {
         )
     };
 
+    // The id can be taken apart to be stored by a node, and rebuilt
+    let persist_id = rusk_vm::NetworkStateId::new(
+        persist_id.nonce(),
+        persist_id.code_count(),
+        persist_id.contracts().clone(),
+        persist_id.codes().clone(),
+        persist_id.code_index().clone(),
+    );
+
     // If the persistence works, We should still read 100 with a freshly created
     // NetworkState.
     let mut network = NetworkState::with_block_height(10)